@@ -2,7 +2,8 @@
  *
  * jgpmf C API: parses GoPro GPMF sample payloads.
  *
- * Returned pointers from jgpmf_sample_accl / _gyro / _grav / _cori / _iori
+ * Returned pointers from jgpmf_sample_accl / _gyro / _grav / _cori / _iori / _magn
+ * / _shut / _isoe / _isog / _wbal / _wrgb
  * alias into memory owned by the JgpmfSample and become invalid after the
  * corresponding jgpmf_sample_free call.
  */
@@ -98,6 +99,15 @@ typedef struct {
   float z;
 } JgpmfQuat;
 
+/**
+ * White-balance gains of one frame.
+ */
+typedef struct {
+  float r;
+  float g;
+  float b;
+} JgpmfRgb;
+
 #ifdef __cplusplus
 extern "C" {
 #endif // __cplusplus
@@ -184,6 +194,82 @@ JgpmfStatus jgpmf_sample_iori(const JgpmfSample *sample,
                               const JgpmfQuat **out_ptr,
                               size_t *out_count);
 
+/**
+ * Borrows the sample's magnetometer array (µT after SCAL, raw `z, x, y`
+ * axis order).
+ *
+ * # Safety
+ * See [`jgpmf_sample_accl`].
+ */
+JgpmfStatus jgpmf_sample_magn(const JgpmfSample *sample,
+                              const JgpmfVec3 **out_ptr,
+                              size_t *out_count);
+
+/**
+ * Copies the tilt-compensated compass heading (degrees clockwise from
+ * magnetic north, one per `MAGN` sample, `NaN` where undefined) into
+ * `out_buf`, which has room for `capacity` values.
+ *
+ * Always writes the total number of headings to `*out_count`; at most
+ * `capacity` of them are copied. Pass `capacity == 0` (and a null
+ * `out_buf`) to query the count. The count is 0 when the sample lacks
+ * `MAGN` or `GRAV`.
+ *
+ * # Safety
+ * `out_count` must be writable. Unless `capacity` is 0, `out_buf` must point
+ * to at least `capacity` writable floats.
+ */
+JgpmfStatus jgpmf_sample_heading(const JgpmfSample *sample,
+                                 float *out_buf,
+                                 size_t capacity,
+                                 size_t *out_count);
+
+/**
+ * Borrows the sample's exposure time array (s), one value per video frame
+ * in frame order.
+ *
+ * # Safety
+ * See [`jgpmf_sample_accl`].
+ */
+JgpmfStatus jgpmf_sample_shut(const JgpmfSample *sample, const float **out_ptr, size_t *out_count);
+
+/**
+ * Borrows the sample's sensor ISO array, one value per video frame (HERO7
+ * and later). Empty on older cameras, which report `ISOG` instead.
+ *
+ * # Safety
+ * See [`jgpmf_sample_accl`].
+ */
+JgpmfStatus jgpmf_sample_isoe(const JgpmfSample *sample, const float **out_ptr, size_t *out_count);
+
+/**
+ * Borrows the sample's ISO gain array, one value per video frame (before
+ * HERO7). Empty on newer cameras, which report `ISOE` instead.
+ *
+ * # Safety
+ * See [`jgpmf_sample_accl`].
+ */
+JgpmfStatus jgpmf_sample_isog(const JgpmfSample *sample, const float **out_ptr, size_t *out_count);
+
+/**
+ * Borrows the sample's white-balance array (K), one value per video frame.
+ *
+ * # Safety
+ * See [`jgpmf_sample_accl`].
+ */
+JgpmfStatus jgpmf_sample_wbal(const JgpmfSample *sample, const float **out_ptr, size_t *out_count);
+
+/**
+ * Borrows the sample's white-balance RGB gains array, one value per video
+ * frame.
+ *
+ * # Safety
+ * See [`jgpmf_sample_accl`].
+ */
+JgpmfStatus jgpmf_sample_wrgb(const JgpmfSample *sample,
+                              const JgpmfRgb **out_ptr,
+                              size_t *out_count);
+
 /**
  * Library semantic version. Any out-parameter may be null.
  *
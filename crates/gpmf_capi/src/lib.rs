@@ -3,7 +3,9 @@
 //! The host application demuxes the GoPro `gpmd` track itself (e.g. via
 //! FFmpeg) and passes one GPMF sample payload at a time to
 //! `jgpmf_sample_parse`. Per-sample telemetry (`GPS9`, `ACCL`, `GYRO`,
-//! `GRAV`, `CORI`, `IORI`) is then queried through dedicated getters.
+//! `GRAV`, `CORI`, `IORI`, `MAGN` and the compass heading derived from it,
+//! and the per-frame `SHUT`, `ISOE`, `ISOG`, `WBAL` and `WRGB`) is then
+//! queried through dedicated getters.
 //!
 //! All `extern "C"` entry points are panic-safe — Rust panics are caught and
 //! reported as `JGPMF_ERR_PARSE` rather than unwinding across the FFI
@@ -13,7 +15,7 @@
 
 use std::panic::{catch_unwind, AssertUnwindSafe};
 
-use gpmf_parser::{GpmfSample, Klv, Quat as ParserQuat, Vec3 as ParserVec3, klv::Value};
+use gpmf_parser::{GpmfSample, Klv, Quat as ParserQuat, Vec3 as ParserVec3, imaging::Rgb as ParserRgb, klv::Value};
 
 // --- Layout-compatibility assertions ---------------------------------------
 //
@@ -25,6 +27,8 @@ const _: () = {
     assert!(core::mem::align_of::<JgpmfVec3>() == core::mem::align_of::<ParserVec3>());
     assert!(core::mem::size_of::<JgpmfQuat>() == core::mem::size_of::<ParserQuat>());
     assert!(core::mem::align_of::<JgpmfQuat>() == core::mem::align_of::<ParserQuat>());
+    assert!(core::mem::size_of::<JgpmfRgb>() == core::mem::size_of::<ParserRgb>());
+    assert!(core::mem::align_of::<JgpmfRgb>() == core::mem::align_of::<ParserRgb>());
 };
 
 // --- Status codes ----------------------------------------------------------
@@ -81,6 +85,14 @@ pub struct JgpmfQuat {
     pub z: f32,
 }
 
+/// White-balance gains of one frame.
+#[repr(C)]
+pub struct JgpmfRgb {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+}
+
 // --- Opaque handle ---------------------------------------------------------
 
 /// Opaque parsed-sample handle. Allocated by `jgpmf_sample_parse`, freed by
@@ -128,8 +140,7 @@ pub unsafe extern "C" fn jgpmf_sample_parse(
     }
     let result = catch_unwind(AssertUnwindSafe(|| {
         let slice = unsafe { std::slice::from_raw_parts(bytes, len) };
-        let mut cursor = std::io::Cursor::new(slice);
-        let klvs = match Klv::from_reader(&mut cursor) {
+        let klvs = match Klv::from_bytes(slice) {
             Ok(v) => v,
             Err(_) => return JgpmfStatus::JGPMF_ERR_PARSE,
         };
@@ -242,6 +253,36 @@ unsafe fn export_quat(
     JgpmfStatus::JGPMF_OK
 }
 
+unsafe fn export_rgb(
+    slice: &[ParserRgb],
+    out_ptr: *mut *const JgpmfRgb,
+    out_count: *mut usize,
+) -> JgpmfStatus {
+    if out_ptr.is_null() || out_count.is_null() {
+        return JgpmfStatus::JGPMF_ERR_NULL_ARG;
+    }
+    unsafe {
+        *out_ptr = slice.as_ptr() as *const JgpmfRgb;
+        *out_count = slice.len();
+    }
+    JgpmfStatus::JGPMF_OK
+}
+
+unsafe fn export_f32(
+    slice: &[f32],
+    out_ptr: *mut *const f32,
+    out_count: *mut usize,
+) -> JgpmfStatus {
+    if out_ptr.is_null() || out_count.is_null() {
+        return JgpmfStatus::JGPMF_ERR_NULL_ARG;
+    }
+    unsafe {
+        *out_ptr = slice.as_ptr();
+        *out_count = slice.len();
+    }
+    JgpmfStatus::JGPMF_OK
+}
+
 /// Borrows the sample's accelerometer array (m/s² after SCAL). The pointer
 /// remains valid until `jgpmf_sample_free` is called on the same handle.
 /// An empty stream produces `*out_count == 0` and `*out_ptr` unspecified.
@@ -324,6 +365,149 @@ pub unsafe extern "C" fn jgpmf_sample_iori(
     unsafe { export_quat(s.iori(), out_ptr, out_count) }
 }
 
+/// Borrows the sample's magnetometer array (µT after SCAL, raw `z, x, y`
+/// axis order).
+///
+/// # Safety
+/// See [`jgpmf_sample_accl`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn jgpmf_sample_magn(
+    sample: *const JgpmfSample,
+    out_ptr: *mut *const JgpmfVec3,
+    out_count: *mut usize,
+) -> JgpmfStatus {
+    let Some(s) = from_handle(sample) else {
+        return JgpmfStatus::JGPMF_ERR_NULL_ARG;
+    };
+    unsafe { export_vec3(s.magn(), out_ptr, out_count) }
+}
+
+/// Copies the tilt-compensated compass heading (degrees clockwise from
+/// magnetic north, one per `MAGN` sample, `NaN` where undefined) into
+/// `out_buf`, which has room for `capacity` values.
+///
+/// Always writes the total number of headings to `*out_count`; at most
+/// `capacity` of them are copied. Pass `capacity == 0` (and a null
+/// `out_buf`) to query the count. The count is 0 when the sample lacks
+/// `MAGN` or `GRAV`.
+///
+/// # Safety
+/// `out_count` must be writable. Unless `capacity` is 0, `out_buf` must point
+/// to at least `capacity` writable floats.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn jgpmf_sample_heading(
+    sample: *const JgpmfSample,
+    out_buf: *mut f32,
+    capacity: usize,
+    out_count: *mut usize,
+) -> JgpmfStatus {
+    if out_count.is_null() || (out_buf.is_null() && capacity != 0) {
+        return JgpmfStatus::JGPMF_ERR_NULL_ARG;
+    }
+    let Some(s) = from_handle(sample) else {
+        return JgpmfStatus::JGPMF_ERR_NULL_ARG;
+    };
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        let heading = s.heading();
+        let copied = heading.len().min(capacity);
+        unsafe {
+            if copied > 0 {
+                std::ptr::copy_nonoverlapping(heading.as_ptr(), out_buf, copied);
+            }
+            *out_count = heading.len();
+        }
+        JgpmfStatus::JGPMF_OK
+    }));
+    match result {
+        Ok(s) => s,
+        Err(_) => status_of_panic(),
+    }
+}
+
+/// Borrows the sample's exposure time array (s), one value per video frame
+/// in frame order.
+///
+/// # Safety
+/// See [`jgpmf_sample_accl`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn jgpmf_sample_shut(
+    sample: *const JgpmfSample,
+    out_ptr: *mut *const f32,
+    out_count: *mut usize,
+) -> JgpmfStatus {
+    let Some(s) = from_handle(sample) else {
+        return JgpmfStatus::JGPMF_ERR_NULL_ARG;
+    };
+    unsafe { export_f32(s.shut(), out_ptr, out_count) }
+}
+
+/// Borrows the sample's sensor ISO array, one value per video frame (HERO7
+/// and later). Empty on older cameras, which report `ISOG` instead.
+///
+/// # Safety
+/// See [`jgpmf_sample_accl`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn jgpmf_sample_isoe(
+    sample: *const JgpmfSample,
+    out_ptr: *mut *const f32,
+    out_count: *mut usize,
+) -> JgpmfStatus {
+    let Some(s) = from_handle(sample) else {
+        return JgpmfStatus::JGPMF_ERR_NULL_ARG;
+    };
+    unsafe { export_f32(s.isoe(), out_ptr, out_count) }
+}
+
+/// Borrows the sample's ISO gain array, one value per video frame (before
+/// HERO7). Empty on newer cameras, which report `ISOE` instead.
+///
+/// # Safety
+/// See [`jgpmf_sample_accl`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn jgpmf_sample_isog(
+    sample: *const JgpmfSample,
+    out_ptr: *mut *const f32,
+    out_count: *mut usize,
+) -> JgpmfStatus {
+    let Some(s) = from_handle(sample) else {
+        return JgpmfStatus::JGPMF_ERR_NULL_ARG;
+    };
+    unsafe { export_f32(s.isog(), out_ptr, out_count) }
+}
+
+/// Borrows the sample's white-balance array (K), one value per video frame.
+///
+/// # Safety
+/// See [`jgpmf_sample_accl`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn jgpmf_sample_wbal(
+    sample: *const JgpmfSample,
+    out_ptr: *mut *const f32,
+    out_count: *mut usize,
+) -> JgpmfStatus {
+    let Some(s) = from_handle(sample) else {
+        return JgpmfStatus::JGPMF_ERR_NULL_ARG;
+    };
+    unsafe { export_f32(s.wbal(), out_ptr, out_count) }
+}
+
+/// Borrows the sample's white-balance RGB gains array, one value per video
+/// frame.
+///
+/// # Safety
+/// See [`jgpmf_sample_accl`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn jgpmf_sample_wrgb(
+    sample: *const JgpmfSample,
+    out_ptr: *mut *const JgpmfRgb,
+    out_count: *mut usize,
+) -> JgpmfStatus {
+    let Some(s) = from_handle(sample) else {
+        return JgpmfStatus::JGPMF_ERR_NULL_ARG;
+    };
+    unsafe { export_rgb(s.wrgb(), out_ptr, out_count) }
+}
+
 /// Library semantic version. Any out-parameter may be null.
 ///
 /// # Safety
@@ -1,18 +1,79 @@
 //! Exercises the `extern "C"` entry points through raw pointer arguments,
-//! the same way a C caller would. Uses the bundled GPMF sample binary from
-//! `gpmf_parser`.
-//!
-//! Gated behind the `with-fixtures` feature because the referenced
-//! `test_files/sample_60.bin` is intentionally not tracked in git.
-
-#![cfg(feature = "with-fixtures")]
+//! the same way a C caller would. Uses payloads built with
+//! `gpmf_parser::synth`, plus the bundled GPMF sample binary from
+//! `gpmf_parser` when the `with-fixtures` feature is enabled (the referenced
+//! `test_files/sample_60.bin` is intentionally not tracked in git).
 
 use std::ptr;
 
+use gpmf_parser::synth::{self, DevcBuilder};
 use jgpmf_capi::*;
 
+#[cfg(feature = "with-fixtures")]
 const SAMPLE_BYTES: &[u8] = include_bytes!("../../gpmf_parser/test_files/sample_60.bin");
 
+fn empty_gps9() -> JgpmfGps9 {
+    JgpmfGps9 {
+        fix: 0, dop: 0.0, latitude: 0.0, longitude: 0.0, altitude: 0.0,
+        speed_2d: 0.0, speed_3d: 0.0, days_since_2000: 0.0, seconds_since_midnight: 0.0,
+    }
+}
+
+#[test]
+fn parse_and_read_back_synthetic_telemetry() {
+    let bytes = DevcBuilder::typical(0).build();
+
+    let mut handle: *mut JgpmfSample = ptr::null_mut();
+    let status = unsafe { jgpmf_sample_parse(bytes.as_ptr(), bytes.len(), &mut handle) };
+    assert!(matches!(status, JgpmfStatus::JGPMF_OK));
+    assert!(!handle.is_null());
+
+    let mut gps = empty_gps9();
+    assert!(matches!(unsafe { jgpmf_sample_get_gps9(handle, &mut gps) }, JgpmfStatus::JGPMF_OK));
+    assert_eq!(gps.fix, 3);
+    assert!((gps.latitude - 35.0).abs() < 1e-5);
+
+    let mut accl_ptr: *const JgpmfVec3 = ptr::null();
+    let mut accl_count: usize = 0;
+    assert!(matches!(
+        unsafe { jgpmf_sample_accl(handle, &mut accl_ptr, &mut accl_count) },
+        JgpmfStatus::JGPMF_OK
+    ));
+    assert_eq!(accl_count, 200);
+    let accl = unsafe { std::slice::from_raw_parts(accl_ptr, accl_count) };
+    assert!((accl[0].x - 9.81).abs() < 0.01);
+
+    let mut q_ptr: *const JgpmfQuat = ptr::null();
+    let mut q_count: usize = 0;
+    assert!(matches!(
+        unsafe { jgpmf_sample_cori(handle, &mut q_ptr, &mut q_count) },
+        JgpmfStatus::JGPMF_OK
+    ));
+    assert_eq!(q_count, 60);
+
+    unsafe { jgpmf_sample_free(handle) };
+}
+
+#[test]
+fn sample_without_gps9_reports_no_gps9() {
+    let bytes = DevcBuilder::new(1, "Camera").accl(&synth::accl_at_rest(10)).build();
+
+    let mut handle: *mut JgpmfSample = ptr::null_mut();
+    assert!(matches!(
+        unsafe { jgpmf_sample_parse(bytes.as_ptr(), bytes.len(), &mut handle) },
+        JgpmfStatus::JGPMF_OK
+    ));
+
+    let mut gps = empty_gps9();
+    assert!(matches!(
+        unsafe { jgpmf_sample_get_gps9(handle, &mut gps) },
+        JgpmfStatus::JGPMF_ERR_NO_GPS9
+    ));
+
+    unsafe { jgpmf_sample_free(handle) };
+}
+
+#[cfg(feature = "with-fixtures")]
 #[test]
 fn parse_and_read_back_full_telemetry() {
     let mut handle: *mut JgpmfSample = ptr::null_mut();
@@ -55,10 +116,7 @@ fn parse_and_read_back_full_telemetry() {
     ));
 
     // GPS9 may legitimately have no fix in test fixtures; accept either OK or NO_GPS9.
-    let mut gps = JgpmfGps9 {
-        fix: 0, dop: 0.0, latitude: 0.0, longitude: 0.0, altitude: 0.0,
-        speed_2d: 0.0, speed_3d: 0.0, days_since_2000: 0.0, seconds_since_midnight: 0.0,
-    };
+    let mut gps = empty_gps9();
     let status = unsafe { jgpmf_sample_get_gps9(handle, &mut gps) };
     assert!(matches!(
         status,
@@ -70,13 +128,14 @@ fn parse_and_read_back_full_telemetry() {
 
 #[test]
 fn null_args_return_null_arg_status() {
+    let bytes = DevcBuilder::typical(0).build();
     let mut handle: *mut JgpmfSample = ptr::null_mut();
     assert!(matches!(
         unsafe { jgpmf_sample_parse(ptr::null(), 0, &mut handle) },
         JgpmfStatus::JGPMF_ERR_NULL_ARG
     ));
     assert!(matches!(
-        unsafe { jgpmf_sample_parse(SAMPLE_BYTES.as_ptr(), SAMPLE_BYTES.len(), ptr::null_mut()) },
+        unsafe { jgpmf_sample_parse(bytes.as_ptr(), bytes.len(), ptr::null_mut()) },
         JgpmfStatus::JGPMF_ERR_NULL_ARG
     ));
 
@@ -122,3 +181,74 @@ fn version_returns_sensible_values() {
 fn free_is_null_safe() {
     unsafe { jgpmf_sample_free(ptr::null_mut()) };
 }
+
+#[test]
+fn magn_and_heading() {
+    // Raw MAGN order is z, x, y; north lies along -x, 90° left of the lens.
+    let magn = [gpmf_parser::Vec3 { x: 0.0, y: -30.0, z: 10.0 }; 24];
+    let grav = [gpmf_parser::Vec3 { x: 0.0, y: 1.0, z: 0.0 }; 60];
+    let bytes = DevcBuilder::new(1, "Camera").magn(&magn).grav(&grav).build();
+
+    let mut handle: *mut JgpmfSample = ptr::null_mut();
+    assert!(matches!(
+        unsafe { jgpmf_sample_parse(bytes.as_ptr(), bytes.len(), &mut handle) },
+        JgpmfStatus::JGPMF_OK
+    ));
+
+    let mut magn_ptr: *const JgpmfVec3 = ptr::null();
+    let mut magn_count: usize = 0;
+    assert!(matches!(
+        unsafe { jgpmf_sample_magn(handle, &mut magn_ptr, &mut magn_count) },
+        JgpmfStatus::JGPMF_OK
+    ));
+    assert_eq!(magn_count, 24);
+
+    let mut count: usize = 0;
+    assert!(matches!(
+        unsafe { jgpmf_sample_heading(handle, ptr::null_mut(), 0, &mut count) },
+        JgpmfStatus::JGPMF_OK
+    ));
+    assert_eq!(count, 24);
+
+    let mut heading = [0.0f32; 4];
+    assert!(matches!(
+        unsafe { jgpmf_sample_heading(handle, heading.as_mut_ptr(), heading.len(), &mut count) },
+        JgpmfStatus::JGPMF_OK
+    ));
+    assert_eq!(count, 24);
+    assert!(heading.iter().all(|h| (h - 90.0).abs() < 0.01));
+
+    unsafe { jgpmf_sample_free(handle) };
+}
+
+#[test]
+fn per_frame_exposure() {
+    let bytes = DevcBuilder::new(1, "Camera")
+        .shut(&[0.01, 0.02])
+        .isoe(&[100, 200])
+        .wbal(&[5500, 5600])
+        .wrgb(&[gpmf_parser::imaging::Rgb { r: 2.0, g: 1.0, b: 1.5 }; 2])
+        .build();
+
+    let mut handle: *mut JgpmfSample = ptr::null_mut();
+    assert!(matches!(
+        unsafe { jgpmf_sample_parse(bytes.as_ptr(), bytes.len(), &mut handle) },
+        JgpmfStatus::JGPMF_OK
+    ));
+
+    let mut f_ptr: *const f32 = ptr::null();
+    let mut count: usize = 0;
+    assert!(matches!(unsafe { jgpmf_sample_isoe(handle, &mut f_ptr, &mut count) }, JgpmfStatus::JGPMF_OK));
+    assert_eq!(unsafe { std::slice::from_raw_parts(f_ptr, count) }, [100.0, 200.0]);
+    assert!(matches!(unsafe { jgpmf_sample_wbal(handle, &mut f_ptr, &mut count) }, JgpmfStatus::JGPMF_OK));
+    assert_eq!(unsafe { std::slice::from_raw_parts(f_ptr, count) }, [5500.0, 5600.0]);
+    assert!(matches!(unsafe { jgpmf_sample_isog(handle, &mut f_ptr, &mut count) }, JgpmfStatus::JGPMF_OK));
+    assert_eq!(count, 0);
+
+    let mut rgb_ptr: *const JgpmfRgb = ptr::null();
+    assert!(matches!(unsafe { jgpmf_sample_wrgb(handle, &mut rgb_ptr, &mut count) }, JgpmfStatus::JGPMF_OK));
+    assert_eq!(count, 2);
+    assert_eq!(unsafe { (*rgb_ptr).r }, 2.0);
+
+    unsafe { jgpmf_sample_free(handle) };
+}
@@ -0,0 +1,103 @@
+//! Audio-side streams written by HERO8 and later: `WNDM` wind processing,
+//! `MWET` wet microphones and `AALP` audio levels.
+//!
+//! All three are sampled at a fixed rate (about 10 Hz) independent of the
+//! video frame rate.
+
+use alloc::vec::Vec;
+
+use crate::decode::{DecodedStream, StreamContext};
+use crate::klv::Fourcc;
+use crate::GpmfSample;
+
+/// A `WNDM` sample.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Wind {
+    /// Whether wind suppression was being applied.
+    pub enabled: bool,
+    /// Wind meter, in `[0, 100]`.
+    pub level: f32,
+}
+
+/// An `MWET` sample.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MicWet {
+    /// Whether at least one microphone was wet.
+    pub wet: bool,
+    /// Whether all microphones were wet.
+    pub all_mics: bool,
+    /// Confidence of the detection, in `[0, 100]`.
+    pub confidence: f32,
+}
+
+/// An `AALP` sample.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AudioLevel {
+    /// RMS level in _dBFS_.
+    pub rms: f32,
+    /// Peak level in _dBFS_.
+    pub peak: f32,
+}
+
+impl GpmfSample {
+    /// Empty slice when the source has no `WNDM` stream.
+    pub fn wndm(&self) -> &[Wind] {
+        self.decoded_as::<Vec<Wind>>(Fourcc(*b"WNDM")).map_or(&[], Vec::as_slice)
+    }
+
+    /// Empty slice when the source has no `MWET` stream.
+    pub fn mwet(&self) -> &[MicWet] {
+        self.decoded_as::<Vec<MicWet>>(Fourcc(*b"MWET")).map_or(&[], Vec::as_slice)
+    }
+
+    /// Empty slice when the source has no `AALP` stream.
+    pub fn aalp(&self) -> &[AudioLevel] {
+        self.decoded_as::<Vec<AudioLevel>>(Fourcc(*b"AALP")).map_or(&[], Vec::as_slice)
+    }
+}
+
+/// Decodes a `WNDM` stream into a `Vec<Wind>`.
+pub(crate) fn decode_wind(stream: &StreamContext<'_>) -> Option<DecodedStream> {
+    let tuples = crate::extract_axes(stream, 2)?;
+    let winds: Vec<Wind> = tuples.into_iter().map(|t| Wind { enabled: t[0] != 0.0, level: t[1] }).collect();
+    Some(DecodedStream::new(winds))
+}
+
+/// Decodes an `MWET` stream into a `Vec<MicWet>`.
+pub(crate) fn decode_mic_wet(stream: &StreamContext<'_>) -> Option<DecodedStream> {
+    let tuples = crate::extract_axes(stream, 3)?;
+    let flags: Vec<MicWet> = tuples
+        .into_iter()
+        .map(|t| MicWet { wet: t[0] != 0.0, all_mics: t[1] != 0.0, confidence: t[2] })
+        .collect();
+    Some(DecodedStream::new(flags))
+}
+
+/// Decodes an `AALP` stream into a `Vec<AudioLevel>`.
+pub(crate) fn decode_audio_level(stream: &StreamContext<'_>) -> Option<DecodedStream> {
+    let tuples = crate::extract_axes(stream, 2)?;
+    let levels: Vec<AudioLevel> = tuples.into_iter().map(|t| AudioLevel { rms: t[0], peak: t[1] }).collect();
+    Some(DecodedStream::new(levels))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::synth::DevcBuilder;
+    use crate::Klv;
+
+    #[test]
+    fn audio_streams_are_decoded() {
+        let wind = [Wind { enabled: false, level: 0.0 }, Wind { enabled: true, level: 65.0 }];
+        let wet = [MicWet { wet: true, all_mics: false, confidence: 80.0 }];
+        let levels = [AudioLevel { rms: -32.0, peak: -6.0 }; 10];
+        let bytes = DevcBuilder::new(1, "Camera").wndm(&wind).mwet(&wet).aalp(&levels).build();
+        let klvs = Klv::from_bytes(&bytes).unwrap();
+        let sample = GpmfSample::new(&klvs[0]);
+
+        assert_eq!(sample.wndm(), wind);
+        assert_eq!(sample.mwet(), wet);
+        assert_eq!(sample.aalp(), levels);
+    }
+}
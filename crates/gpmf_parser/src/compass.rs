@@ -0,0 +1,141 @@
+//! `MAGN` magnetometer stream and the compass heading derived from it.
+
+use alloc::vec::Vec;
+
+use crate::{GpmfSample, Vec3};
+
+impl GpmfSample {
+    /// Magnetometer samples (in _µT_ after SCAL is applied), in raw KLV axis
+    /// order, i.e. `z, x, y` as GoPro documents it.
+    /// Empty slice when the source has no `MAGN` stream.
+    pub fn magn(&self) -> &[Vec3] {
+        self.vec3s(*b"MAGN")
+    }
+
+    /// Tilt-compensated compass heading of the lens, in degrees clockwise
+    /// from magnetic north in `[0, 360)`, one per `MAGN` sample.
+    ///
+    /// Each `MAGN` sample is paired with the `GRAV` sample at the same relative
+    /// position in the payload, since the two streams have different rates.
+    /// `MAGN` is re-ordered from `z, x, y` into the `x, y, z` frame of `GRAV`,
+    /// in which the lens points along `+z`.
+    ///
+    /// Empty when either stream is missing. Headings that are undefined (e.g.
+    /// the lens pointing straight up) are `NaN`.
+    pub fn heading(&self) -> Vec<f32> {
+        let (magn, grav) = (self.magn(), self.grav());
+        if magn.is_empty() || grav.is_empty() {
+            return Vec::new();
+        }
+
+        const LENS: Vec3 = Vec3 { x: 0.0, y: 0.0, z: 1.0 };
+
+        magn.iter()
+            .enumerate()
+            .map(|(i, m)| {
+                let g = grav[i * grav.len() / magn.len()];
+                let m = Vec3 { x: m.y, y: m.z, z: m.x };
+                tilt_compensated_heading(m, g, LENS).unwrap_or(f32::NAN)
+            })
+            .collect()
+    }
+}
+
+/// Heading of `forward`, in degrees clockwise from magnetic north in
+/// `[0, 360)`.
+///
+/// `magnetic` and `gravity` must be in the same frame as `forward`, with
+/// `gravity` pointing towards the ground. Only their directions matter.
+/// Returns `None` if `forward` or `magnetic` is (close to) vertical.
+pub fn tilt_compensated_heading(magnetic: Vec3, gravity: Vec3, forward: Vec3) -> Option<f32> {
+    let down = normalize(gravity)?;
+    let east = normalize(cross(down, magnetic))?;
+    let north = cross(east, down);
+
+    let (e, n) = (dot(east, forward), dot(north, forward));
+    if libm::hypotf(e, n) < 1e-6 {
+        return None;
+    }
+
+    let heading = libm::atan2f(e, n).to_degrees();
+    Some(if heading < 0.0 { heading + 360.0 } else { heading })
+}
+
+fn dot(a: Vec3, b: Vec3) -> f32 {
+    a.x * b.x + a.y * b.y + a.z * b.z
+}
+
+fn cross(a: Vec3, b: Vec3) -> Vec3 {
+    Vec3 {
+        x: a.y * b.z - a.z * b.y,
+        y: a.z * b.x - a.x * b.z,
+        z: a.x * b.y - a.y * b.x,
+    }
+}
+
+fn normalize(v: Vec3) -> Option<Vec3> {
+    let length = libm::sqrtf(dot(v, v));
+    (length > 1e-6).then(|| Vec3 { x: v.x / length, y: v.y / length, z: v.z / length })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::synth::DevcBuilder;
+    use crate::Klv;
+
+    const DOWN: Vec3 = Vec3 { x: 0.0, y: 1.0, z: 0.0 };
+    const LENS: Vec3 = Vec3 { x: 0.0, y: 0.0, z: 1.0 };
+
+    #[test]
+    fn heading_follows_horizontal_field() {
+        let north_ahead = Vec3 { x: 0.0, y: 0.0, z: 30.0 };
+        let north_to_left = Vec3 { x: -30.0, y: 0.0, z: 0.0 };
+        let north_to_right = Vec3 { x: 30.0, y: 0.0, z: 0.0 };
+
+        assert!(tilt_compensated_heading(north_ahead, DOWN, LENS).unwrap().abs() < 1e-3);
+        assert!((tilt_compensated_heading(north_to_left, DOWN, LENS).unwrap() - 90.0).abs() < 1e-3);
+        assert!((tilt_compensated_heading(north_to_right, DOWN, LENS).unwrap() - 270.0).abs() < 1e-3);
+    }
+
+    /// Rotates `v` about the x axis by `degrees`.
+    fn rotate_x(v: Vec3, degrees: f32) -> Vec3 {
+        let (s, c) = libm::sincosf(degrees.to_radians());
+        Vec3 { x: v.x, y: v.y * c - v.z * s, z: v.y * s + v.z * c }
+    }
+
+    #[test]
+    fn heading_compensates_for_tilt_and_dip() {
+        // Lens pitched 30° towards the ground, field dipping 60° below north.
+        let forward = rotate_x(LENS, -30.0);
+        let magnetic = rotate_x(Vec3 { x: 0.0, y: 0.0, z: 45.0 }, -60.0);
+        let heading = tilt_compensated_heading(magnetic, DOWN, forward).unwrap();
+        assert!(heading.abs() < 1e-3, "got {heading}");
+
+        // The heading doesn't depend on the frame the vectors are expressed in.
+        let heading = tilt_compensated_heading(rotate_x(magnetic, 70.0), rotate_x(DOWN, 70.0), rotate_x(forward, 70.0)).unwrap();
+        assert!(heading.abs() < 1e-3 || (heading - 360.0).abs() < 1e-3, "got {heading}");
+    }
+
+    #[test]
+    fn heading_is_undefined_when_looking_straight_down() {
+        assert!(tilt_compensated_heading(LENS, DOWN, DOWN).is_none());
+    }
+
+    #[test]
+    fn magn_is_decoded_and_paired_with_grav() {
+        // Raw MAGN order is z, x, y: north to the left of the lens (-x).
+        let magn = [Vec3 { x: 0.0, y: -30.0, z: 10.0 }; 24];
+        let bytes = DevcBuilder::new(1, "Camera").magn(&magn).grav(&[DOWN; 60]).build();
+        let klvs = Klv::from_bytes(&bytes).unwrap();
+        let sample = GpmfSample::new(&klvs[0]);
+
+        assert_eq!(sample.magn().len(), 24);
+        assert!((sample.magn()[0].y + 30.0).abs() < 1e-3);
+
+        let heading = sample.heading();
+        assert_eq!(heading.len(), 24);
+        assert!(heading.iter().all(|h| (h - 90.0).abs() < 1e-2));
+    }
+}
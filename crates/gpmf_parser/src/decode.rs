@@ -0,0 +1,411 @@
+//! Pluggable decoding of `STRM` blocks into typed values.
+//!
+//! A [`GpmfSampleParser`] holds one [`StreamDecoder`] per FourCC. When a
+//! `STRM` contains a KLV with a registered FourCC, the decoder is handed the
+//! whole `STRM` and its output is stored on the resulting [`GpmfSample`],
+//! from where it can be retrieved with [`GpmfSample::decoded_as`].
+
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+
+use core::any::Any;
+use core::fmt::Debug;
+
+use crate::klv::{Fourcc, Klv, Value};
+use crate::visit::{KlvVisitor, StickyScope};
+use crate::GpmfSample;
+
+/// Decodes the `STRM` blocks carrying a given FourCC.
+pub trait StreamDecoder: Send + Sync {
+    /// Returns `None` if the stream doesn't have the expected shape, in which
+    /// case nothing is stored for it.
+    fn decode(&self, stream: &StreamContext<'_>) -> Option<DecodedStream>;
+}
+
+impl<F> StreamDecoder for F
+where
+    F: Fn(&StreamContext<'_>) -> Option<DecodedStream> + Send + Sync,
+{
+    fn decode(&self, stream: &StreamContext<'_>) -> Option<DecodedStream> {
+        self(stream)
+    }
+}
+
+/// What a [`StreamDecoder`] gets to see of a `STRM`.
+#[derive(Debug, Clone, Copy)]
+pub struct StreamContext<'a> {
+    fourcc: Fourcc,
+    klvs: &'a [Klv],
+    scope: &'a StickyScope<'a>,
+}
+
+impl<'a> StreamContext<'a> {
+    /// The FourCC the decoder was registered for.
+    pub fn fourcc(&self) -> Fourcc {
+        self.fourcc
+    }
+
+    /// All children of the `STRM`.
+    pub fn klvs(&self) -> &'a [Klv] {
+        self.klvs
+    }
+
+    /// The first child KLV with the registered FourCC.
+    pub fn data(&self) -> &'a Klv {
+        self.data_klvs().next().expect("A STRM is only decoded when it has a data KLV.")
+    }
+
+    /// Every child KLV with the registered FourCC. Streams such as `FACE`
+    /// store one KLV per video frame.
+    pub fn data_klvs(&self) -> impl Iterator<Item = &'a Klv> + 'a {
+        let fourcc = self.fourcc;
+        self.klvs.iter().filter(move |klv| klv.header().fourcc() == fourcc)
+    }
+
+    /// The nearest non-nested KLV with `fourcc` preceding the data KLV, in
+    /// the `STRM` or the enclosing `DEVC`. This is usually sticky metadata
+    /// (`SCAL`, `TYPE`, `SIUN`, ...), but every earlier KLV is in scope, so in a
+    /// `STRM` with several data KLVs it can be an earlier one.
+    pub fn sticky(&self, fourcc: &str) -> Option<&'a Klv> {
+        self.scope.get(fourcc)
+    }
+
+    pub fn scope(&self) -> &'a StickyScope<'a> {
+        self.scope
+    }
+}
+
+/// Splits the children of a `STRM` into its data KLV, the last child, and the
+/// sticky metadata before it. Generic over the KLV representation so raw
+/// headers can be split the same way as parsed [`Klv`]s.
+pub fn split_stream<T>(children: &[T]) -> Option<(&T, &[T])> {
+    children.split_last()
+}
+
+/// The children of a `STRM`, split with [`split_stream`].
+#[derive(Debug, Clone, Copy)]
+pub struct StreamKlvs<'a> {
+    children: &'a [Klv],
+    data: &'a Klv,
+    sticky: &'a [Klv],
+}
+
+impl<'a> StreamKlvs<'a> {
+    /// `None` if the `STRM` is empty or ends with a nested KLV.
+    pub fn new(children: &'a [Klv]) -> Option<Self> {
+        let (data, sticky) = split_stream(children)?;
+        (!matches!(data.value(), Value::Nested(_))).then_some(Self { children, data, sticky })
+    }
+
+    /// The last child.
+    pub fn data(&self) -> &'a Klv {
+        self.data
+    }
+
+    /// Every child with the FourCC of [`StreamKlvs::data`].
+    pub fn data_klvs(&self) -> impl Iterator<Item = &'a Klv> + 'a {
+        let fourcc = self.data.header().fourcc();
+        self.children.iter().filter(move |klv| klv.header().fourcc() == fourcc)
+    }
+
+    /// Total `repeat` of [`StreamKlvs::data_klvs`].
+    pub fn sample_count(&self) -> u64 {
+        self.data_klvs().map(|klv| klv.header().tsr().repeat() as u64).sum()
+    }
+
+    /// The children before [`StreamKlvs::data`].
+    pub fn sticky_klvs(&self) -> &'a [Klv] {
+        self.sticky
+    }
+
+    /// The nearest sticky KLV with `fourcc` preceding the data KLV.
+    pub fn sticky(&self, fourcc: &str) -> Option<&'a Klv> {
+        self.sticky.iter().rev().find(|klv| klv.header().fourcc().as_str() == fourcc)
+    }
+
+    /// `STNM`, `TYPE` or another sticky string, without its NUL padding.
+    pub fn sticky_text(&self, fourcc: &str) -> Option<&'a str> {
+        match self.sticky(fourcc)?.value() {
+            Value::Ascii(text) => Some(text.trim_end_matches('\0')),
+            _ => None,
+        }
+    }
+
+    /// The unit of each element, from `SIUN` or else `UNIT`. Each unit takes
+    /// `sample_size` characters, padded with NULs, which are kept.
+    pub fn units(&self) -> Option<Vec<String>> {
+        let klv = self.sticky("SIUN").or_else(|| self.sticky("UNIT"))?;
+        let Value::Ascii(text) = klv.value() else {
+            return None;
+        };
+        let size = (klv.header().tsr().sample_size() as usize).max(1);
+        let chars: Vec<char> = text.chars().collect();
+        Some(chars.chunks(size).map(|unit| unit.iter().collect()).collect())
+    }
+}
+
+/// Type-erased output of a [`StreamDecoder`]. Cheap to clone.
+#[derive(Clone)]
+pub struct DecodedStream(Arc<dyn DecodedValue>);
+
+trait DecodedValue: Any + Debug + Send + Sync {
+    fn as_any(&self) -> &dyn Any;
+}
+
+impl<T: Any + Debug + Send + Sync> DecodedValue for T {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl DecodedStream {
+    pub fn new<T: Any + Debug + Send + Sync>(value: T) -> Self {
+        Self(Arc::new(value))
+    }
+
+    pub fn downcast_ref<T: Any>(&self) -> Option<&T> {
+        (*self.0).as_any().downcast_ref()
+    }
+}
+
+impl Debug for DecodedStream {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// Builds [`GpmfSample`]s using a configurable set of [`StreamDecoder`]s.
+///
+/// [`GpmfSampleParser::default`] has the built-in decoders registered; use
+/// [`GpmfSampleParser::empty`] to start from scratch.
+pub struct GpmfSampleParser {
+    decoders: BTreeMap<Fourcc, Box<dyn StreamDecoder>>,
+}
+
+impl Default for GpmfSampleParser {
+    fn default() -> Self {
+        let mut parser = Self::empty();
+        crate::register_builtin_decoders(&mut parser);
+        parser
+    }
+}
+
+impl Debug for GpmfSampleParser {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("GpmfSampleParser")
+            .field("decoders", &self.decoders.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl GpmfSampleParser {
+    pub fn empty() -> Self {
+        Self {
+            decoders: BTreeMap::new(),
+        }
+    }
+
+    /// A [`GpmfSampleParser::default`] built on first use and shared by every
+    /// caller afterwards.
+    #[cfg(feature = "std")]
+    pub fn builtin() -> &'static Self {
+        static BUILTIN: std::sync::OnceLock<GpmfSampleParser> = std::sync::OnceLock::new();
+        BUILTIN.get_or_init(Self::default)
+    }
+
+    /// Registers `decoder` for `STRM`s containing `fourcc`, replacing any
+    /// decoder previously registered for it.
+    pub fn register(&mut self, fourcc: Fourcc, decoder: impl StreamDecoder + 'static) -> &mut Self {
+        self.decoders.insert(fourcc, Box::new(decoder));
+        self
+    }
+
+    pub fn unregister(&mut self, fourcc: Fourcc) -> &mut Self {
+        self.decoders.remove(&fourcc);
+        self
+    }
+
+    /// ## Panics
+    /// - If the given KLV is not a nested `DEVC` one.
+    pub fn parse(&self, devc_klv: &Klv) -> GpmfSample {
+        let decoded = self.decode(devc_klv);
+        let Value::Nested(child_klvs) = devc_klv.value() else {
+            unreachable!()
+        };
+
+        GpmfSample {
+            klvs: child_klvs.clone(),
+            decoded,
+        }
+    }
+
+    /// [`GpmfSampleParser::parse`] taking ownership of the `DEVC` KLV, so its
+    /// children are moved into the sample instead of cloned.
+    ///
+    /// ## Panics
+    /// - If the given KLV is not a nested `DEVC` one.
+    pub fn parse_owned(&self, devc_klv: Klv) -> GpmfSample {
+        let decoded = self.decode(&devc_klv);
+        let Value::Nested(child_klvs) = devc_klv.into_value() else {
+            unreachable!()
+        };
+
+        GpmfSample {
+            klvs: child_klvs,
+            decoded,
+        }
+    }
+
+    fn decode(&self, devc_klv: &Klv) -> BTreeMap<Fourcc, DecodedStream> {
+        assert_eq!(devc_klv.header().fourcc().as_str(), "DEVC");
+        assert!(
+            matches!(devc_klv.value(), Value::Nested(_)),
+            "DEVC KLV with Nested value is expected."
+        );
+
+        let mut visitor = DecodeVisitor {
+            parser: self,
+            strm: None,
+            decoded: BTreeMap::new(),
+        };
+        devc_klv.walk(&mut visitor);
+        visitor.decoded
+    }
+}
+
+struct DecodeVisitor<'p, 'a> {
+    parser: &'p GpmfSampleParser,
+    /// Children of the `STRM` being walked, and the FourCCs already decoded from it.
+    strm: Option<(&'a [Klv], Vec<Fourcc>)>,
+    decoded: BTreeMap<Fourcc, DecodedStream>,
+}
+
+impl<'a> KlvVisitor<'a> for DecodeVisitor<'_, 'a> {
+    fn enter_container(&mut self, _path: &[Fourcc], klv: &'a Klv, _scope: &StickyScope<'a>) -> bool {
+        if klv.header().fourcc().as_str() == "STRM"
+            && let Value::Nested(children) = klv.value()
+        {
+            self.strm = Some((children, Vec::new()));
+        }
+        true
+    }
+
+    fn visit_value(&mut self, _path: &[Fourcc], klv: &'a Klv, scope: &StickyScope<'a>) {
+        let Some((strm_children, attempted)) = &mut self.strm else {
+            return;
+        };
+
+        let fourcc = klv.header().fourcc();
+        if attempted.contains(&fourcc) || self.decoded.contains_key(&fourcc) {
+            return;
+        }
+        let Some(decoder) = self.parser.decoders.get(&fourcc) else {
+            return;
+        };
+        attempted.push(fourcc);
+
+        let stream = StreamContext {
+            fourcc,
+            klvs: strm_children,
+            scope,
+        };
+        if let Some(decoded) = decoder.decode(&stream) {
+            self.decoded.insert(fourcc, decoded);
+        }
+    }
+
+    fn leave_container(&mut self, _path: &[Fourcc], klv: &'a Klv) {
+        if klv.header().fourcc().as_str() == "STRM" {
+            self.strm = None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct Temperature(f32);
+
+    #[rustfmt::skip]
+    const DEVC_WITH_TMPC: &[u8] = &[
+        b'D', b'E', b'V', b'C', 0, 1, 0, 44,
+            b'S', b'T', b'R', b'M', 0, 1, 0, 36,
+                b'S', b'C', b'A', b'L', b's', 2, 0, 1, 0, 10, 0, 0,
+                b'T', b'M', b'P', b'C', b'f', 4, 0, 1, 0x42, 0x20, 0, 0,
+                b'A', b'C', b'C', b'L', b's', 2, 0, 1, 0, 1, 0, 0,
+    ];
+
+    #[test]
+    fn custom_decoder_output_is_stored_on_sample() {
+        let klvs = Klv::from_bytes(DEVC_WITH_TMPC).unwrap();
+
+        let mut parser = GpmfSampleParser::empty();
+        parser.register(Fourcc(*b"TMPC"), |stream: &StreamContext<'_>| {
+            assert!(stream.sticky("SCAL").is_some());
+            let Value::F32(v) = stream.data().value() else { return None };
+            Some(DecodedStream::new(Temperature(v[0])))
+        });
+        let sample = parser.parse(&klvs[0]);
+
+        assert_eq!(sample.decoded_as::<Temperature>(Fourcc(*b"TMPC")), Some(&Temperature(40.0)));
+        assert!(sample.decoded_as::<f32>(Fourcc(*b"TMPC")).is_none());
+        assert!(sample.accl().is_empty(), "ACCL decoder isn't registered on an empty parser");
+        assert_eq!(sample.gps9().fix, 0);
+    }
+
+    #[test]
+    fn default_parser_decodes_builtin_streams() {
+        let klvs = Klv::from_bytes(DEVC_WITH_TMPC).unwrap();
+
+        let sample = GpmfSampleParser::default().parse(&klvs[0]);
+
+        assert_eq!(sample.accl().len(), 0, "ACCL with 1 value isn't a Vec3 stream");
+        assert_eq!(sample.tmpc(), Some(40.0), "TMPC isn't subject to the stream's SCAL");
+    }
+
+    #[test]
+    fn stream_klvs_split_data_from_sticky_metadata() {
+        use crate::synth;
+
+        let strm = [
+            synth::ascii(b"TYPE", "bb"),
+            synth::klv(b"SIUN", b'c', 4, 2, b"m/s\0rad\0"),
+            synth::s16s(b"ACCL", &[1, 2]),
+            synth::ascii(b"TYPE", "ss"),
+            synth::s16s(b"ACCL", &[3, 4, 5, 6]),
+        ]
+        .concat();
+        let children = Klv::from_bytes(&strm).unwrap();
+
+        let stream = StreamKlvs::new(&children).unwrap();
+        assert_eq!(stream.data().header().fourcc(), Fourcc(*b"ACCL"));
+        assert_eq!(stream.sticky_klvs().len(), 4);
+        assert_eq!(stream.sample_count(), 6);
+        assert_eq!(stream.sticky_text("TYPE"), Some("ss"), "the nearest preceding TYPE wins");
+        assert_eq!(stream.units(), Some(alloc::vec!["m/s\0".into(), "rad\0".into()]));
+        assert!(StreamKlvs::new(&[]).is_none());
+    }
+
+    #[test]
+    fn parse_owned_matches_parse() {
+        let mut klvs = Klv::from_bytes(DEVC_WITH_TMPC).unwrap();
+        let parser = GpmfSampleParser::default();
+
+        let borrowed = parser.parse(&klvs[0]);
+        let owned = parser.parse_owned(klvs.remove(0));
+
+        assert_eq!(owned.klvs().len(), borrowed.klvs().len());
+        assert_eq!(owned.tmpc(), borrowed.tmpc());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn builtin_parser_is_built_once() {
+        assert!(core::ptr::eq(GpmfSampleParser::builtin(), GpmfSampleParser::builtin()));
+    }
+}
@@ -0,0 +1,191 @@
+//! `FACE` stream: face bounding boxes detected in each video frame.
+//!
+//! Each frame is stored as its own `FACE` KLV, whose repeat is the number of
+//! faces found (possibly 0). The struct layout, described by the stream's
+//! `TYPE`, depends on the camera generation:
+//!
+//! | Camera     | Fields                                          |
+//! |------------|-------------------------------------------------|
+//! | HERO6      | `ID, x, y, w, h`                                |
+//! | HERO7      | `ID, x, y, w, h, unused[17], smile`             |
+//! | HERO8      | `ver, confidence %, ID, x, y, w, h, smile %, blink %` |
+//! | HERO10 and later | `ver, confidence %, ID, x, y, w, h`       |
+//!
+//! `SCAL` is applied per field, after which `x`, `y`, `w` and `h` are in
+//! `[0, 1]` relative to the frame.
+
+use alloc::vec::Vec;
+
+use crate::decode::{DecodedStream, StreamContext};
+use crate::klv::{Fourcc, Value};
+use crate::GpmfSample;
+
+/// A detected face. `x` and `y` are the top-left corner of its bounding box.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Face {
+    pub id: u32,
+    pub x: f32,
+    pub y: f32,
+    pub w: f32,
+    pub h: f32,
+    /// Detection confidence in _%_. HERO8 and later only.
+    pub confidence: Option<f32>,
+    /// Smile strength, as written by the camera (_%_ on HERO8).
+    /// HERO7 and HERO8 only.
+    pub smile: Option<f32>,
+}
+
+/// `FACE` struct layouts across camera generations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FaceLayout {
+    Hero6,
+    Hero7,
+    Hero8,
+    Hero10,
+}
+
+impl FaceLayout {
+    /// Recognizes a layout by its number of struct fields.
+    pub fn from_field_count(field_count: usize) -> Option<Self> {
+        match field_count {
+            5 => Some(Self::Hero6),
+            23 => Some(Self::Hero7),
+            9 => Some(Self::Hero8),
+            7 => Some(Self::Hero10),
+            _ => None,
+        }
+    }
+
+    /// The `TYPE` string the camera writes for this layout.
+    pub fn type_str(&self) -> &'static str {
+        match self {
+            Self::Hero6 => "Lffff",
+            Self::Hero7 => "Lfffff[17]f",
+            Self::Hero8 => "BBSSSSSBB",
+            Self::Hero10 => "BBSSSSS",
+        }
+    }
+
+    /// Builds a [`Face`] from one struct, with `SCAL` already applied.
+    fn face(&self, fields: &[f64]) -> Face {
+        let bbox = |start: usize| (fields[start] as f32, fields[start + 1] as f32, fields[start + 2] as f32, fields[start + 3] as f32);
+        let (id, (x, y, w, h), confidence, smile) = match self {
+            Self::Hero6 => (fields[0], bbox(1), None, None),
+            Self::Hero7 => (fields[0], bbox(1), None, Some(fields[22] as f32)),
+            Self::Hero8 => (fields[2], bbox(3), Some(fields[1] as f32), Some(fields[7] as f32)),
+            Self::Hero10 => (fields[2], bbox(3), Some(fields[1] as f32), None),
+        };
+        Face {
+            id: id as u32,
+            x,
+            y,
+            w,
+            h,
+            confidence,
+            smile,
+        }
+    }
+}
+
+impl GpmfSample {
+    /// Faces detected in each video frame covered by the payload.
+    /// Empty slice when the source has no `FACE` stream.
+    pub fn faces(&self) -> &[Vec<Face>] {
+        self.decoded_as::<Vec<Vec<Face>>>(Fourcc(*b"FACE")).map_or(&[], Vec::as_slice)
+    }
+}
+
+/// Decodes a `FACE` stream into a `Vec<Vec<Face>>`, one entry per frame.
+pub(crate) fn decode_faces(stream: &StreamContext<'_>) -> Option<DecodedStream> {
+    let Value::Ascii(type_str) = stream.sticky("TYPE")?.value() else {
+        return None;
+    };
+    let scal = stream.sticky("SCAL").and_then(|klv| crate::numbers_as_f32(klv.value())).unwrap_or_default();
+
+    let mut layout = None;
+    let mut frames = Vec::new();
+    for klv in stream.data_klvs() {
+        let Value::Complex(value) = klv.value() else {
+            return None;
+        };
+        let structs = value.structs(type_str).ok()?;
+
+        let mut faces = Vec::with_capacity(structs.len());
+        for mut fields in structs {
+            let layout = *layout.get_or_insert(FaceLayout::from_field_count(fields.len())?);
+            if !scal.is_empty() {
+                if scal.len() != 1 && scal.len() != fields.len() {
+                    return None;
+                }
+                for (i, field) in fields.iter_mut().enumerate() {
+                    *field /= crate::scal_for_axis(&scal, i) as f64;
+                }
+            }
+            faces.push(layout.face(&fields));
+        }
+        frames.push(faces);
+    }
+    Some(DecodedStream::new(frames))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use alloc::vec;
+
+    use crate::synth::DevcBuilder;
+    use crate::Klv;
+
+    fn parse(bytes: &[u8]) -> GpmfSample {
+        let klvs = Klv::from_bytes(bytes).unwrap();
+        GpmfSample::new(&klvs[0])
+    }
+
+    fn face(id: u32, confidence: Option<f32>, smile: Option<f32>) -> Face {
+        Face {
+            id,
+            x: 0.25,
+            y: 0.5,
+            w: 0.125,
+            h: 0.25,
+            confidence,
+            smile,
+        }
+    }
+
+    #[test]
+    fn faces_are_decoded_for_every_layout() {
+        for (layout, expected) in [
+            (FaceLayout::Hero6, face(3, None, None)),
+            (FaceLayout::Hero7, face(3, None, Some(0.5))),
+            (FaceLayout::Hero8, face(3, Some(87.0), Some(40.0))),
+            (FaceLayout::Hero10, face(3, Some(87.0), None)),
+        ] {
+            let frames = vec![vec![expected], vec![], vec![expected, Face { id: 4, ..expected }]];
+            let sample = parse(&DevcBuilder::new(1, "Camera").faces(layout, &frames).build());
+
+            let faces = sample.faces();
+            assert_eq!(faces.len(), 3, "{layout:?}");
+            for (decoded, expected) in faces.iter().flatten().zip(frames.iter().flatten()) {
+                assert_eq!((decoded.id, decoded.confidence, decoded.smile), (expected.id, expected.confidence, expected.smile));
+                for (a, b) in [(decoded.x, expected.x), (decoded.y, expected.y), (decoded.w, expected.w), (decoded.h, expected.h)] {
+                    assert!((a - b).abs() < 1e-4, "{layout:?}: {decoded:?}");
+                }
+            }
+            assert_eq!(faces.iter().map(Vec::len).collect::<Vec<_>>(), [1, 0, 2]);
+        }
+    }
+
+    #[test]
+    fn unknown_face_layout_is_not_decoded() {
+        use crate::synth::{ascii, klv, StreamBuilder};
+
+        let strm = StreamBuilder::new()
+            .klv(ascii(b"TYPE", "LLL"))
+            .klv(klv(b"FACE", b'?', 12, 1, &[0; 12]));
+        let sample = parse(&DevcBuilder::new(1, "Camera").stream(strm).build());
+
+        assert!(sample.faces().is_empty());
+    }
+}
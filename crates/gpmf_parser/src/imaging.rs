@@ -0,0 +1,110 @@
+//! Image-processing streams: `SHUT`, `ISOE`, `ISOG`, `WBAL`, `WRGB`, `YAVG`,
+//! `UNIF`, and the `MSKP`/`LSKP` frame skips.
+//!
+//! Each of these holds one value per video frame covered by the payload.
+
+use alloc::vec::Vec;
+
+use crate::decode::{DecodedStream, StreamContext};
+use crate::GpmfSample;
+
+/// White-balance gains of a `WRGB` sample.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[repr(C)]
+pub struct Rgb {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+}
+
+impl GpmfSample {
+    /// Exposure time of each frame, in _s_.
+    /// Empty slice when the source has no `SHUT` stream.
+    pub fn shut(&self) -> &[f32] {
+        self.scalars(*b"SHUT")
+    }
+
+    /// Sensor ISO of each frame, written by HERO7 and later.
+    pub fn isoe(&self) -> &[f32] {
+        self.scalars(*b"ISOE")
+    }
+
+    /// ISO gain of each frame, written by cameras before the HERO7.
+    pub fn isog(&self) -> &[f32] {
+        self.scalars(*b"ISOG")
+    }
+
+    /// White balance of each frame, in _K_.
+    pub fn wbal(&self) -> &[f32] {
+        self.scalars(*b"WBAL")
+    }
+
+    /// Average luma of each frame, in `[0, 255]`.
+    pub fn yavg(&self) -> &[f32] {
+        self.scalars(*b"YAVG")
+    }
+
+    /// Image uniformity of each frame, in `[0, 1]` where `1` is a flat image
+    /// such as that of a covered lens.
+    pub fn unif(&self) -> &[f32] {
+        self.scalars(*b"UNIF")
+    }
+
+    /// Number of sensor frames skipped or duplicated before each frame of the
+    /// main video. Normally 0.
+    pub fn mskp(&self) -> &[f32] {
+        self.scalars(*b"MSKP")
+    }
+
+    /// Like [`GpmfSample::mskp`], for the low-resolution proxy video.
+    pub fn lskp(&self) -> &[f32] {
+        self.scalars(*b"LSKP")
+    }
+
+    /// White-balance RGB gains of each frame.
+    pub fn wrgb(&self) -> &[Rgb] {
+        self.decoded_as::<Vec<Rgb>>(crate::klv::Fourcc(*b"WRGB")).map_or(&[], Vec::as_slice)
+    }
+}
+
+/// Decodes a `WRGB` stream into a `Vec<Rgb>`.
+pub(crate) fn decode_rgb(stream: &StreamContext<'_>) -> Option<DecodedStream> {
+    let tuples = crate::extract_axes(stream, 3)?;
+    let rgbs: Vec<Rgb> = tuples.into_iter().map(|t| Rgb { r: t[0], g: t[1], b: t[2] }).collect();
+    Some(DecodedStream::new(rgbs))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::synth::DevcBuilder;
+    use crate::Klv;
+
+    #[test]
+    fn per_frame_exposure_streams_are_decoded() {
+        let gains = Rgb { r: 1.9, g: 1.0, b: 1.6 };
+        let bytes = DevcBuilder::new(1, "Camera")
+            .shut(&[1.0 / 60.0, 1.0 / 120.0, 1.0 / 240.0])
+            .isoe(&[100, 200, 400])
+            .wbal(&[5500, 5500, 5400])
+            .wrgb(&[gains; 3])
+            .yavg(&[12, 128, 250])
+            .unif(&[0.98, 0.3, 0.25])
+            .mskp(&[0, 2, 0])
+            .build();
+        let klvs = Klv::from_bytes(&bytes).unwrap();
+        let sample = GpmfSample::new(&klvs[0]);
+
+        assert_eq!(sample.shut().len(), 3);
+        assert!((sample.shut()[1] - 1.0 / 120.0).abs() < 1e-6);
+        assert_eq!(sample.isoe(), [100.0, 200.0, 400.0]);
+        assert!(sample.isog().is_empty());
+        assert_eq!(sample.wbal(), [5500.0, 5500.0, 5400.0]);
+        assert_eq!(sample.wrgb(), [gains; 3]);
+        assert_eq!(sample.yavg(), [12.0, 128.0, 250.0]);
+        assert_eq!(sample.unif(), [0.98, 0.3, 0.25]);
+        assert_eq!(sample.mskp(), [0.0, 2.0, 0.0]);
+        assert!(sample.lskp().is_empty());
+    }
+}
@@ -1,24 +1,38 @@
+#[cfg(feature = "chrono")]
 pub use chrono::NaiveDateTime;
 
 use thiserror::Error;
 
-use std::io::{Read, Seek};
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
 
-use byteorder::{BigEndian, ReadBytesExt as _};
+use crate::reader::ByteReader;
 
 // https://github.com/gopro/gpmf-parser
 // https://exiftool.org/TagNames/GoPro.html
 
 #[derive(Debug, Error)]
+#[non_exhaustive]
 pub enum KlvError {
+    #[cfg(feature = "std")]
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
-    #[error("Unknown value type: \'{}\'/(0x{:02X})", char::from(0), 0)]
+    #[error("Unexpected end of data at offset {0}")]
+    UnexpectedEof(usize),
+    #[error("Unknown value type: \'{}\'/(0x{:02X})", char::from(*.0).escape_default(), .0)]
     UnknownValueType(u8),
+    #[error("Malformed `[N]` count in TYPE \"{0}\"")]
+    MalformedTypeCount(String),
+    #[error("TYPE \"{0}\" describes a struct larger than the {MAX_SAMPLE_SIZE} bytes a sample can hold")]
+    StructTooLarge(String),
     #[error("FourCC value source is 0x00000000")]
     ZeroFourcc,
 }
 
+/// The largest sample size a KLV header can express.
+pub const MAX_SAMPLE_SIZE: usize = u8::MAX as usize;
+
 #[derive(Debug, Clone)]
 pub struct Klv {
     header: Header,
@@ -26,21 +40,43 @@ pub struct Klv {
 }
 
 impl Klv {
-    pub fn from_reader<R: Read + Seek>(reader: &mut R) -> Result<Vec<Self>, KlvError> {
+    /// Reads the remainder of `reader` into memory and parses it with [`Klv::from_bytes`].
+    #[cfg(feature = "std")]
+    pub fn from_reader<R: std::io::Read>(reader: &mut R) -> Result<Vec<Self>, KlvError> {
+        let mut bytes: Vec<u8> = Default::default();
+        reader.read_to_end(&mut bytes)?;
+
+        Self::from_bytes(&bytes)
+    }
+
+    /// Asynchronous counterpart of [`Klv::from_reader`].
+    #[cfg(feature = "async")]
+    pub async fn from_async_reader<R: tokio::io::AsyncRead + Unpin>(reader: &mut R) -> Result<Vec<Self>, KlvError> {
+        use tokio::io::AsyncReadExt as _;
+
+        let mut bytes: Vec<u8> = Default::default();
+        reader.read_to_end(&mut bytes).await?;
+
+        Self::from_bytes(&bytes)
+    }
+
+    /// Parses top-level KLVs until the data runs out or a zero FourCC is met.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Vec<Self>, KlvError> {
+        let mut reader = ByteReader::new(bytes);
         let mut klvs: Vec<Self> = Default::default();
 
         loop {
-            let header = Header::from_reader(reader);
+            let header = Header::from_reader(&mut reader);
             match header {
                 Err(KlvError::ZeroFourcc) => {
                     break;
                 }
-                Err(KlvError::Io(err)) if err.kind() == std::io::ErrorKind::UnexpectedEof => {
+                Err(KlvError::UnexpectedEof(_)) => {
                     break;
                 }
                 Err(err) => return Err(err),
                 Ok(header) => {
-                    let value = Value::from_reader(reader, header)?;
+                    let value = Value::from_reader(&mut reader, header)?;
                     klvs.push(Self { header, value });
                 }
             }
@@ -70,7 +106,7 @@ pub struct Header {
 
 impl Header {
     /// Reads exactly 8 bytes.
-    fn from_reader<R: Read>(reader: &mut R) -> Result<Self, KlvError> {
+    fn from_reader(reader: &mut ByteReader<'_>) -> Result<Self, KlvError> {
         let fourcc = Fourcc::from_reader(reader)?;
         let tsr = TypeSizeRepeat::from_reader(reader)?;
 
@@ -91,9 +127,8 @@ pub struct Fourcc(pub [u8; 4]);
 
 impl Fourcc {
     /// Reads exactly 4 bytes.
-    pub fn from_reader<R: Read>(reader: &mut R) -> Result<Self, KlvError> {
-        let mut bytes = [0; 4];
-        reader.read_exact(&mut bytes)?;
+    pub fn from_reader(reader: &mut ByteReader<'_>) -> Result<Self, KlvError> {
+        let bytes: [u8; 4] = reader.read_array()?;
 
         if bytes == [0; 4] {
             return Err(KlvError::ZeroFourcc);
@@ -107,16 +142,16 @@ impl Fourcc {
     }
 
     pub fn as_str(&self) -> &str {
-        std::str::from_utf8(&self.0).expect("Fourcc is not a valid UTF-8 string.")
+        core::str::from_utf8(&self.0).expect("Fourcc is not a valid UTF-8 string.")
     }
 }
 
-impl std::fmt::Debug for Fourcc {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Debug for Fourcc {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(
             f,
             "{}/0x{:04X}",
-            std::str::from_utf8(&self.0).unwrap(),
+            core::str::from_utf8(&self.0).unwrap(),
             u32::from_be_bytes(self.0)
         )
     }
@@ -135,7 +170,7 @@ pub struct TypeSizeRepeat {
 
 impl TypeSizeRepeat {
     /// Reads exactly 4 bytes.
-    pub fn from_reader<R: Read>(reader: &mut R) -> Result<Self, KlvError> {
+    pub fn from_reader(reader: &mut ByteReader<'_>) -> Result<Self, KlvError> {
         let type_u8 = reader.read_u8()?;
         let typ = ValueType::try_from(type_u8);
         if typ.is_err() {
@@ -143,7 +178,7 @@ impl TypeSizeRepeat {
         };
         let typ = typ.unwrap();
         let sample_size = reader.read_u8()?;
-        let repeat = reader.read_u16::<BigEndian>()?;
+        let repeat = reader.read_u16()?;
 
         Ok(Self {
             typ,
@@ -152,6 +187,25 @@ impl TypeSizeRepeat {
         })
     }
 
+    pub fn value_type(&self) -> ValueType {
+        self.typ
+    }
+
+    /// Size in bytes of one sample, i.e. of one struct for `Complex` values.
+    pub fn sample_size(&self) -> u8 {
+        self.sample_size
+    }
+
+    /// Number of samples.
+    pub fn repeat(&self) -> u16 {
+        self.repeat
+    }
+
+    /// Size in bytes of the value, excluding the padding to 4 bytes.
+    pub fn data_size(&self) -> usize {
+        self.sample_size as usize * self.repeat as usize
+    }
+
     pub fn axis_count(&self) -> usize {
         let single_size = self.typ.element_size();
         if single_size == 0 {
@@ -207,6 +261,63 @@ impl TryFrom<u8> for ValueType {
 }
 
 impl ValueType {
+    /// Expands a `TYPE` string such as `"lllllllSS"` or `"Lf[4]"` into the
+    /// types of the fields of the struct it describes.
+    ///
+    /// Fails on structs larger than [`MAX_SAMPLE_SIZE`], so a hostile `[N]`
+    /// count can't exhaust memory.
+    pub fn parse_struct_type(type_str: &str) -> Result<Vec<Self>, KlvError> {
+        let bytes = type_str.as_bytes();
+        let mut types = Vec::with_capacity(bytes.len());
+        let mut struct_size = 0usize;
+        let mut i = 0;
+        while i < bytes.len() {
+            let typ = Self::try_from(bytes[i]).map_err(|_| KlvError::UnknownValueType(bytes[i]))?;
+            if matches!(typ, Self::Complex | Self::Nested) {
+                return Err(KlvError::UnknownValueType(bytes[i]));
+            }
+            i += 1;
+
+            let mut count: usize = 1;
+            if bytes.get(i) == Some(&b'[') {
+                let malformed = || KlvError::MalformedTypeCount(type_str.into());
+                let close = bytes[i..].iter().position(|&b| b == b']').ok_or_else(malformed)?;
+                count = core::str::from_utf8(&bytes[i + 1..i + close])
+                    .ok()
+                    .and_then(|n| n.parse().ok())
+                    .ok_or_else(malformed)?;
+                i += close + 1;
+            }
+            struct_size = count
+                .checked_mul(typ.element_size())
+                .and_then(|size| size.checked_add(struct_size))
+                .filter(|&size| size <= MAX_SAMPLE_SIZE)
+                .ok_or_else(|| KlvError::StructTooLarge(type_str.into()))?;
+            types.extend(core::iter::repeat_n(typ, count));
+        }
+        Ok(types)
+    }
+
+    /// The type character used in KLV headers and `TYPE` strings, e.g. `'s'`
+    /// for `S16`. `Nested` is `'\0'`.
+    pub const fn as_char(&self) -> char {
+        match self {
+            Self::S8 => 'b',
+            Self::U8 => 'B',
+            Self::S32 => 'l',
+            Self::U32 => 'L',
+            Self::Ascii => 'c',
+            Self::F32 => 'f',
+            Self::Fourcc => 'F',
+            Self::U64 => 'J',
+            Self::S16 => 's',
+            Self::U16 => 'S',
+            Self::DateTime => 'U',
+            Self::Complex => '?',
+            Self::Nested => '\0',
+        }
+    }
+
     /// Returns `0` for `Complex` and `Nested`.
     pub const fn element_size(&self) -> usize {
         match self {
@@ -239,22 +350,52 @@ pub enum Value {
     U64(Vec<u64>),
     S16(Vec<i16>),
     U16(Vec<u16>),
-    DateTime(NaiveDateTime),
+    /// Raw `yymmddhhmmss.sss` string. See [`Value::as_naive_datetime`].
+    DateTime(String),
     Complex(ComplexValue),
     Nested(Vec<Klv>),
 }
 
 impl Value {
-    pub fn from_reader<R: Read + Seek>(reader: &mut R, header: Header) -> Result<Self, KlvError> {
+    /// The values of a numeric KLV as `f64`, in storage order. `None` for
+    /// strings, FourCCs, dates, complex and nested values.
+    pub fn numbers(&self) -> Option<Vec<f64>> {
+        fn convert<T: Copy + Into<f64>>(values: &[T]) -> Option<Vec<f64>> {
+            Some(values.iter().map(|&v| v.into()).collect())
+        }
+
+        match self {
+            Self::S8(v) => convert(v),
+            Self::U8(v) => convert(v),
+            Self::S16(v) => convert(v),
+            Self::U16(v) => convert(v),
+            Self::S32(v) => convert(v),
+            Self::U32(v) => convert(v),
+            Self::F32(v) => convert(v),
+            Self::U64(v) => Some(v.iter().map(|&v| v as f64).collect()),
+            _ => None,
+        }
+    }
+
+    /// Parses a [`Value::DateTime`]. `None` for other values and malformed
+    /// dates.
+    #[cfg(feature = "chrono")]
+    pub fn as_naive_datetime(&self) -> Option<NaiveDateTime> {
+        match self {
+            Self::DateTime(string) => NaiveDateTime::parse_from_str(string, "%y%m%d%H%M%S%.f").ok(),
+            _ => None,
+        }
+    }
+
+    pub fn from_reader(reader: &mut ByteReader<'_>, header: Header) -> Result<Self, KlvError> {
         match header.tsr.typ {
             ValueType::S8 => Ok(Self::S8(Self::read_numeric(reader, header)?)),
             ValueType::U8 => Ok(Self::U8(Self::read_numeric(reader, header)?)),
             ValueType::S32 => Ok(Self::S32(Self::read_numeric(reader, header)?)),
             ValueType::U32 => Ok(Self::U32(Self::read_numeric(reader, header)?)),
             ValueType::Ascii => {
-                let mut bytes =
-                    vec![0; header.tsr.sample_size as usize * header.tsr.repeat as usize];
-                reader.read_exact(&mut bytes)?;
+                let bytes =
+                    reader.read_bytes(header.tsr.sample_size as usize * header.tsr.repeat as usize)?;
                 Self::skip_padding(reader, bytes.len())?;
 
                 /// Converts from Latin1(ISO-8859-1) to UTF-8.
@@ -263,24 +404,23 @@ impl Value {
                     bytes.iter().map(|&b| b as char).collect()
                 }
 
-                Ok(Self::Ascii(latin1_to_utf8(&bytes)))
+                Ok(Self::Ascii(latin1_to_utf8(bytes)))
             }
             ValueType::F32 => Ok(Self::F32(Self::read_numeric(reader, header)?)),
             ValueType::Fourcc => {
                 let axis_count = header.tsr.axis_count();
                 let value_count = axis_count * (header.tsr.repeat as usize);
                 let values: Vec<Fourcc> = (0..value_count)
-                    .map(|_| Fourcc::from_reader(reader).unwrap())
-                    .collect();
+                    .map(|_| Fourcc::from_reader(reader))
+                    .collect::<Result<_, _>>()?;
                 Ok(Self::Fourcc(values))
             }
             ValueType::U64 => Ok(Self::U64(Self::read_numeric(reader, header)?)),
             ValueType::S16 => Ok(Self::S16(Self::read_numeric(reader, header)?)),
             ValueType::U16 => Ok(Self::U16(Self::read_numeric(reader, header)?)),
             ValueType::DateTime => {
-                let mut bytes =
-                    vec![0; header.tsr.sample_size as usize * header.tsr.repeat as usize];
-                reader.read_exact(&mut bytes)?;
+                let bytes =
+                    reader.read_bytes(header.tsr.sample_size as usize * header.tsr.repeat as usize)?;
                 Self::skip_padding(reader, bytes.len())?;
 
                 /// Converts from Latin1(ISO-8859-1) to UTF-8.
@@ -289,32 +429,25 @@ impl Value {
                     bytes.iter().map(|&b| b as char).collect()
                 }
 
-                let string = latin1_to_utf8(&bytes);
-                let date_time = NaiveDateTime::parse_from_str(&string, "%y%m%d%H%M%S%.f").unwrap();
-
-                Ok(Self::DateTime(date_time))
+                Ok(Self::DateTime(latin1_to_utf8(bytes)))
             }
             ValueType::Complex => {
-                let mut bytes =
-                    vec![0; header.tsr.sample_size as usize * header.tsr.repeat as usize];
-                reader.read_exact(&mut bytes)?;
+                let bytes =
+                    reader.read_bytes(header.tsr.sample_size as usize * header.tsr.repeat as usize)?;
                 Self::skip_padding(reader, bytes.len())?;
 
-                Ok(Self::Complex(ComplexValue { raw_data: bytes }))
+                Ok(Self::Complex(ComplexValue { raw_data: bytes.to_vec() }))
             }
             ValueType::Nested => {
                 let mut klvs: Vec<Klv> = Vec::new();
 
-                let mut position = reader.stream_position()?;
-                let end_position = position
-                    + (header.tsr.sample_size as u16 * header.tsr.repeat).next_multiple_of(4)
-                        as u64;
-                while position < end_position {
-                    let header = Header::from_reader(reader)?;
-                    let value = Value::from_reader(reader, header)?;
+                let nested_size = (header.tsr.sample_size as usize * header.tsr.repeat as usize)
+                    .next_multiple_of(4);
+                let mut nested_reader = reader.sub_reader(nested_size)?;
+                while !nested_reader.is_empty() {
+                    let header = Header::from_reader(&mut nested_reader)?;
+                    let value = Value::from_reader(&mut nested_reader, header)?;
                     klvs.push(Klv { header, value });
-
-                    position = reader.stream_position()?;
                 }
 
                 Ok(Self::Nested(klvs))
@@ -322,31 +455,23 @@ impl Value {
         }
     }
 
-    fn read_numeric<T: Numeric + std::fmt::Debug, R: Read>(
-        reader: &mut R,
+    fn read_numeric<T: Numeric + core::fmt::Debug>(
+        reader: &mut ByteReader<'_>,
         header: Header,
-    ) -> Result<Vec<T>, std::io::Error> {
+    ) -> Result<Vec<T>, KlvError> {
         let axis_count = header.tsr.axis_count();
         let value_count = axis_count * (header.tsr.repeat as usize);
 
-        let mut values: Vec<T> = Vec::with_capacity(value_count);
-        T::values_from_reader(reader, unsafe {
-            std::slice::from_raw_parts_mut(values.as_mut_ptr(), value_count)
-        })?;
-        unsafe {
-            values.set_len(value_count);
-        }
+        let values = T::values_from_reader(reader, value_count)?;
 
-        Self::skip_padding(reader, std::mem::size_of_val(values.as_slice()))?;
+        Self::skip_padding(reader, core::mem::size_of_val(values.as_slice()))?;
         Ok(values)
     }
 
-    fn skip_padding<R: Read>(reader: &mut R, bytes_processed: usize) -> Result<(), std::io::Error> {
+    fn skip_padding(reader: &mut ByteReader<'_>, bytes_processed: usize) -> Result<(), KlvError> {
         let padding_size = bytes_processed.next_multiple_of(4) - bytes_processed;
 
-        let mut max_padding: [u8; 4] = [0; 4];
-        reader.read_exact(&mut max_padding[0..padding_size])?;
-        Ok(())
+        reader.skip(padding_size)
     }
 }
 
@@ -359,67 +484,102 @@ impl ComplexValue {
     pub fn raw_data(&self) -> &[u8] {
         &self.raw_data
     }
+
+    /// Reads every struct in this value, laid out as described by the
+    /// stream's `TYPE` string, returning the fields of each as numbers.
+    /// `c` fields read as their Latin-1 code and `F` fields as their
+    /// big-endian `u32`.
+    pub fn structs(&self, type_str: &str) -> Result<Vec<Vec<f64>>, KlvError> {
+        let types = ValueType::parse_struct_type(type_str)?;
+        let struct_size: usize = types.iter().map(ValueType::element_size).sum();
+        if struct_size == 0 {
+            return Ok(Vec::new());
+        }
+
+        let mut reader = ByteReader::new(&self.raw_data);
+        let mut structs = Vec::with_capacity(self.raw_data.len() / struct_size);
+        while reader.remaining() >= struct_size {
+            let fields = types
+                .iter()
+                .map(|typ| {
+                    Ok(match typ {
+                        ValueType::S8 => reader.read_i8()? as f64,
+                        ValueType::U8 | ValueType::Ascii => reader.read_u8()? as f64,
+                        ValueType::S16 => reader.read_i16()? as f64,
+                        ValueType::U16 => reader.read_u16()? as f64,
+                        ValueType::S32 => reader.read_i32()? as f64,
+                        ValueType::U32 | ValueType::Fourcc => reader.read_u32()? as f64,
+                        ValueType::U64 => reader.read_u64()? as f64,
+                        ValueType::F32 => reader.read_f32()? as f64,
+                        ValueType::DateTime => {
+                            reader.skip(16)?;
+                            f64::NAN
+                        }
+                        ValueType::Complex | ValueType::Nested => unreachable!(),
+                    })
+                })
+                .collect::<Result<Vec<f64>, KlvError>>()?;
+            structs.push(fields);
+        }
+        Ok(structs)
+    }
 }
 
 trait Numeric {
-    fn value_from_reader<R: Read>(reader: &mut R) -> Result<Self, std::io::Error>
+    fn value_from_reader(reader: &mut ByteReader<'_>) -> Result<Self, KlvError>
     where
         Self: Sized;
 
-    fn values_from_reader<R: Read>(reader: &mut R, dst: &mut [Self]) -> Result<(), std::io::Error>
+    fn values_from_reader(reader: &mut ByteReader<'_>, count: usize) -> Result<Vec<Self>, KlvError>
     where
         Self: Sized,
     {
-        for value in dst.iter_mut() {
-            *value = Self::value_from_reader(reader)?;
-        }
-        Ok(())
+        (0..count).map(|_| Self::value_from_reader(reader)).collect()
     }
 }
 
 impl Numeric for i8 {
-    fn value_from_reader<R: Read>(reader: &mut R) -> Result<Self, std::io::Error> {
+    fn value_from_reader(reader: &mut ByteReader<'_>) -> Result<Self, KlvError> {
         reader.read_i8()
     }
 }
 impl Numeric for u8 {
-    fn value_from_reader<R: Read>(reader: &mut R) -> Result<Self, std::io::Error> {
+    fn value_from_reader(reader: &mut ByteReader<'_>) -> Result<Self, KlvError> {
         reader.read_u8()
     }
 
-    fn values_from_reader<R: Read>(reader: &mut R, dst: &mut [Self]) -> Result<(), std::io::Error> {
-        reader.read_exact(dst)?;
-        Ok(())
+    fn values_from_reader(reader: &mut ByteReader<'_>, count: usize) -> Result<Vec<Self>, KlvError> {
+        Ok(reader.read_bytes(count)?.to_vec())
     }
 }
 impl Numeric for i32 {
-    fn value_from_reader<R: Read>(reader: &mut R) -> Result<Self, std::io::Error> {
-        reader.read_i32::<BigEndian>()
+    fn value_from_reader(reader: &mut ByteReader<'_>) -> Result<Self, KlvError> {
+        reader.read_i32()
     }
 }
 impl Numeric for u32 {
-    fn value_from_reader<R: Read>(reader: &mut R) -> Result<Self, std::io::Error> {
-        reader.read_u32::<BigEndian>()
+    fn value_from_reader(reader: &mut ByteReader<'_>) -> Result<Self, KlvError> {
+        reader.read_u32()
     }
 }
 impl Numeric for f32 {
-    fn value_from_reader<R: Read>(reader: &mut R) -> Result<Self, std::io::Error> {
-        reader.read_f32::<BigEndian>()
+    fn value_from_reader(reader: &mut ByteReader<'_>) -> Result<Self, KlvError> {
+        reader.read_f32()
     }
 }
 impl Numeric for u64 {
-    fn value_from_reader<R: Read>(reader: &mut R) -> Result<Self, std::io::Error> {
-        reader.read_u64::<BigEndian>()
+    fn value_from_reader(reader: &mut ByteReader<'_>) -> Result<Self, KlvError> {
+        reader.read_u64()
     }
 }
 impl Numeric for i16 {
-    fn value_from_reader<R: Read>(reader: &mut R) -> Result<Self, std::io::Error> {
-        reader.read_i16::<BigEndian>()
+    fn value_from_reader(reader: &mut ByteReader<'_>) -> Result<Self, KlvError> {
+        reader.read_i16()
     }
 }
 impl Numeric for u16 {
-    fn value_from_reader<R: Read>(reader: &mut R) -> Result<Self, std::io::Error> {
-        reader.read_u16::<BigEndian>()
+    fn value_from_reader(reader: &mut ByteReader<'_>) -> Result<Self, KlvError> {
+        reader.read_u16()
     }
 }
 
@@ -427,10 +587,98 @@ impl Numeric for u16 {
 mod tests {
     use super::*;
 
-    use std::io::Cursor;
+    #[test]
+    fn from_bytes_parses_nested_and_padded_values() -> Result<(), KlvError> {
+        #[rustfmt::skip]
+        let bytes: &[u8] = &[
+            b'D', b'E', b'V', b'C', 0, 1, 0, 28,
+                b'D', b'V', b'N', b'M', b'c', 1, 0, 5, b'C', b'a', b'm', b'e', b'r', 0, 0, 0,
+                b'S', b'C', b'A', b'L', b's', 2, 0, 1, 0x01, 0x00, 0, 0,
+            0, 0, 0, 0,
+        ];
+
+        let klvs = Klv::from_bytes(bytes)?;
+        assert_eq!(klvs.len(), 1);
+        let Value::Nested(children) = klvs[0].value() else {
+            panic!("DEVC KLV with Nested value is expected.")
+        };
+        assert!(matches!(children[0].value(), Value::Ascii(s) if s == "Camer"));
+        assert!(matches!(children[1].value(), Value::S16(v) if v == &[256]));
+
+        assert!(matches!(
+            Klv::from_bytes(&bytes[..20]),
+            Err(KlvError::UnexpectedEof(_))
+        ));
+
+        Ok(())
+    }
 
     #[test]
-    fn it_works() -> Result<(), KlvError> {
+    fn type_chars_round_trip() {
+        for &c in b"bBlLcfFJsSU?\0" {
+            assert_eq!(ValueType::try_from(c).unwrap().as_char(), char::from(c));
+        }
+
+        let klvs = Klv::from_bytes(&[b'S', b'C', b'A', b'L', b's', 2, 0, 3, 0, 1, 0, 2, 0, 3, 0, 0]).unwrap();
+        let tsr = klvs[0].header().tsr();
+        assert_eq!((tsr.value_type(), tsr.sample_size(), tsr.repeat()), (ValueType::S16, 2, 3));
+        assert_eq!(tsr.data_size(), 6);
+        assert_eq!(klvs[0].value().numbers(), Some(alloc::vec![1.0, 2.0, 3.0]));
+    }
+
+    #[test]
+    fn struct_types_are_bounded() {
+        assert_eq!(ValueType::parse_struct_type("Lf[2]").unwrap(), alloc::vec![ValueType::U32, ValueType::F32, ValueType::F32]);
+        assert_eq!(ValueType::parse_struct_type("B[255]").unwrap().len(), 255);
+
+        assert!(matches!(ValueType::parse_struct_type("L[18446744073709551615]"), Err(KlvError::StructTooLarge(_))));
+        assert!(matches!(ValueType::parse_struct_type("L[64]"), Err(KlvError::StructTooLarge(_))));
+        assert!(matches!(ValueType::parse_struct_type("B[255]B"), Err(KlvError::StructTooLarge(_))));
+        let error = ValueType::parse_struct_type("L[4").unwrap_err();
+        assert!(matches!(error, KlvError::MalformedTypeCount(_)));
+        assert_eq!(alloc::format!("{}", error), "Malformed `[N]` count in TYPE \"L[4\"");
+        assert_eq!(alloc::format!("{}", KlvError::UnknownValueType(b'x')), "Unknown value type: 'x'/(0x78)");
+    }
+
+    #[test]
+    fn date_times_keep_the_raw_string() {
+        let klv = |date: &[u8; 16]| Klv::from_bytes(&[&b"GPSUU\x10\x00\x01"[..], date].concat()).unwrap().remove(0);
+
+        let gpsu = klv(b"240315123456.789");
+        assert!(matches!(gpsu.value(), Value::DateTime(s) if s == "240315123456.789"));
+        #[cfg(feature = "chrono")]
+        assert_eq!(
+            gpsu.value().as_naive_datetime().unwrap().to_string(),
+            "2024-03-15 12:34:56.789"
+        );
+
+        let malformed = klv(b"not a date\0\0\0\0\0\0");
+        assert!(matches!(malformed.value(), Value::DateTime(_)));
+        #[cfg(feature = "chrono")]
+        assert_eq!(malformed.value().as_naive_datetime(), None);
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn from_async_reader_matches_from_bytes() -> Result<(), KlvError> {
+        let bytes: &[u8] = &[
+            b'T', b'S', b'M', b'P', b'L', 4, 0, 1, 0, 0, 0x01, 0x2C,
+            b'S', b'I', b'U', b'N', b'c', 4, 0, 1, b'm', b'/', b's', b'2',
+        ];
+
+        let klvs = Klv::from_async_reader(&mut &bytes[..]).await?;
+        assert_eq!(klvs.len(), 2);
+        assert!(matches!(klvs[0].value(), Value::U32(v) if v == &[300]));
+        assert!(matches!(klvs[1].value(), Value::Ascii(s) if s == "m/s2"));
+
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "with-fixtures")]
+    fn from_reader_parses_sample_60() -> Result<(), KlvError> {
+        use std::io::Cursor;
+
         //let bytes = include_bytes!("../test_files/gpmf.bin");
         let bytes = include_bytes!("../test_files/sample_60.bin");
 
@@ -438,9 +686,8 @@ mod tests {
             let mut bytes = Cursor::new(bytes);
 
             let klvs = Klv::from_reader(&mut bytes)?;
-            for klv in klvs {
-                println!("{:?} {:?}", klv.header(), klv.value());
-            }
+            assert!(!klvs.is_empty());
+            assert!(klvs.iter().all(|klv| matches!(klv.value(), Value::Nested(_))), "Top-level KLVs should be DEVCs");
         }
 
         Ok(())
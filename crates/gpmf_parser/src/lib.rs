@@ -1,23 +1,40 @@
-pub mod klv;
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
 
+pub mod audio;
+pub mod compass;
+pub mod decode;
+pub mod face;
+pub mod imaging;
+pub mod klv;
+pub mod reader;
+pub mod scene;
+pub mod select;
+#[cfg(any(test, feature = "synth"))]
+pub mod synth;
+pub mod thermal;
+pub mod validate;
+pub mod visit;
+
+pub use decode::{DecodedStream, GpmfSampleParser, StreamContext, StreamDecoder, StreamKlvs};
 pub use klv::Klv;
+pub use select::Selector;
+pub use visit::{KlvVisitor, StickyScope};
 
-use byteorder::{BigEndian, ReadBytesExt as _};
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
 
 #[cfg(feature = "time")]
 use time::{OffsetDateTime, Duration, Date, Month, Time};
 
-use klv::Value;
+use klv::{Fourcc, Value};
+use reader::ByteReader;
 
 #[derive(Debug, Clone)]
 pub struct GpmfSample {
     klvs: Vec<Klv>,
-    gps9: Gps9,
-    accl: Vec<Vec3>,
-    gyro: Vec<Vec3>,
-    grav: Vec<Vec3>,
-    cori: Vec<Quat>,
-    iori: Vec<Quat>,
+    decoded: BTreeMap<Fourcc, DecodedStream>,
 }
 
 /// `GPS9` value, introduced in _GoPro HERO11_.
@@ -64,39 +81,76 @@ pub struct Quat {
     pub z: f32,
 }
 
+/// Reported by [`GpmfSample::gps9`] when the sample has no usable `GPS9` stream.
+const NO_GPS9: Gps9 = Gps9 {
+    fix: 0,
+    dop: 0.0,
+    latitude: 0.0,
+    longitude: 0.0,
+    altitude: 0.0,
+    speed_2d: 0.0,
+    speed_3d: 0.0,
+    days_since_2000: 0.0,
+    seconds_since_midnight: 0.0,
+};
+
 impl GpmfSample {
     pub fn klvs(&self) -> &[Klv] {
         &self.klvs
     }
 
+    /// Output of the [`StreamDecoder`] registered for `fourcc`, if its stream
+    /// was present and decoded successfully.
+    pub fn decoded(&self, fourcc: Fourcc) -> Option<&DecodedStream> {
+        self.decoded.get(&fourcc)
+    }
+
+    /// Like [`GpmfSample::decoded`], downcast to the decoder's output type.
+    pub fn decoded_as<T: core::any::Any>(&self, fourcc: Fourcc) -> Option<&T> {
+        self.decoded(fourcc)?.downcast_ref()
+    }
+
+    /// Has `fix == 0` when the source has no valid `GPS9` stream.
     pub fn gps9(&self) -> &Gps9 {
-        &self.gps9
+        self.decoded_as(Fourcc(*b"GPS9")).unwrap_or(&NO_GPS9)
     }
 
     /// Accelerometer samples (typically ~200 Hz, in _m/s²_ after SCAL is applied).
     /// Empty slice when the source has no `ACCL` stream.
     pub fn accl(&self) -> &[Vec3] {
-        &self.accl
+        self.vec3s(*b"ACCL")
     }
 
     /// Gyroscope samples (typically ~400 Hz, in _rad/s_ after SCAL is applied).
     pub fn gyro(&self) -> &[Vec3] {
-        &self.gyro
+        self.vec3s(*b"GYRO")
     }
 
     /// Gravity vector samples.
     pub fn grav(&self) -> &[Vec3] {
-        &self.grav
+        self.vec3s(*b"GRAV")
     }
 
     /// Camera orientation quaternion samples.
     pub fn cori(&self) -> &[Quat] {
-        &self.cori
+        self.quats(*b"CORI")
     }
 
     /// Image orientation quaternion samples.
     pub fn iori(&self) -> &[Quat] {
-        &self.iori
+        self.quats(*b"IORI")
+    }
+
+    fn vec3s(&self, fourcc: [u8; 4]) -> &[Vec3] {
+        self.decoded_as::<Vec<Vec3>>(Fourcc(fourcc)).map_or(&[], Vec::as_slice)
+    }
+
+    fn scalars(&self, fourcc: [u8; 4]) -> &[f32] {
+        self.decoded_as::<Vec<f32>>(Fourcc(fourcc)).map_or(&[], Vec::as_slice)
+    }
+
+    fn quats(&self, fourcc: [u8; 4]) -> &[Quat] {
+        self.decoded_as::<Vec<Quat>>(Fourcc(fourcc)).map_or(&[], Vec::as_slice)
     }
 }
 
@@ -117,7 +171,7 @@ impl Gps9 {
         let hours = (total_seconds / 3600) as u8;
         let minutes = ((total_seconds % 3600) / 60) as u8;
         let seconds = (total_seconds % 60) as u8;
-        let nanoseconds = ((self.seconds_since_midnight.fract()) * 1_000_000_000.0) as u32;
+        let nanoseconds = ((self.seconds_since_midnight - total_seconds as f32) * 1_000_000_000.0) as u32;
 
         let time = Time::from_hms_nano(hours, minutes, seconds, nanoseconds).ok()?;
 
@@ -127,126 +181,128 @@ impl Gps9 {
 }
 
 impl GpmfSample {
+    /// Parses a sample with the built-in decoders. Use [`GpmfSampleParser`] to
+    /// decode additional streams.
+    ///
+    /// Without the `std` feature the decoders are registered anew on every
+    /// call; parse many samples with one [`GpmfSampleParser`] instead.
+    ///
     /// ## Panics
     /// - If the given KLV is not a nested `DEVC` one.
-    /// - If the `DEVC` KLV does not contain a `STRM` KLV with a valid `GPS9` KLV.
     pub fn new(devc_klv: &Klv) -> Self {
-        assert_eq!(devc_klv.header().fourcc().as_str(), "DEVC");
-
-        let Value::Nested(child_klvs) = devc_klv.value() else {
-            panic!("DEVC KLV with Nested value is expected.")
-        };
-
-        let gps9 = {
-            let strm_klv = child_klvs
-                .iter()
-                .filter(|klv| klv.header().fourcc().as_str() == "STRM")
-                .find(|klv| {
-                    let Value::Nested(strm_klvs) = klv.value() else {
-                        panic!("STRM KLV with Nested value is expected.")
-                    };
-
-                    strm_klvs
-                        .iter()
-                        .any(|klv| klv.header().fourcc().as_str() == "GPS9")
-                });
-
-            let Value::Nested(strm_child_klvs) = strm_klv.unwrap().value() else {
-                panic!("STRM KLV with Nested value is expected.")
-            };
-
-            let gps9_klv = strm_child_klvs
-                .iter()
-                .find(|klv| klv.header().fourcc().as_str() == "GPS9")
-                .unwrap();
-
-            let Value::Complex(complex_value) = gps9_klv.value() else {
-                panic!("GPS9 KLV with Complex value is expected.")
-            };
-
-            {
-                let type_klv = strm_child_klvs
-                    .iter()
-                    .find(|klv| klv.header().fourcc().as_str() == "TYPE")
-                    .unwrap();
-                let Value::Ascii(type_str) = type_klv.value() else {
-                    panic!("TYPE KLV with Ascii value is expected.")
-                };
-
-                assert_eq!(type_str, "lllllllSS");
-            }
-
-            let scal_values = {
-                let scal_klv = strm_child_klvs
-                    .iter()
-                    .find(|klv| klv.header().fourcc().as_str() == "SCAL")
-                    .unwrap();
-                let Value::S32(scal_values) = scal_klv.value() else {
-                    panic!("SCAL KLV with S32 values is expected.")
-                };
-
-                assert_eq!(scal_values.len(), 9);
-
-                scal_values
-            };
-
-            let mut reader = std::io::Cursor::new(complex_value.raw_data());
-
-            let latitude = reader.read_i32::<BigEndian>().unwrap();
-            let longitude = reader.read_i32::<BigEndian>().unwrap();
-            let altitude = reader.read_i32::<BigEndian>().unwrap();
-            let speed_2d = reader.read_i32::<BigEndian>().unwrap();
-            let speed_3d = reader.read_i32::<BigEndian>().unwrap();
-            let days_since_2000 = reader.read_i32::<BigEndian>().unwrap();
-            let seconds_since_midnight = reader.read_i32::<BigEndian>().unwrap();
-            let dop = reader.read_u16::<BigEndian>().unwrap();
-            let fix = reader.read_u16::<BigEndian>().unwrap();
-
-            let latitude = latitude as f32 / scal_values[0] as f32;
-            let longitude = longitude as f32 / scal_values[1] as f32;
-            let altitude = altitude as f32 / scal_values[2] as f32;
-            let speed_2d = speed_2d as f32 / scal_values[3] as f32;
-            let speed_3d = speed_3d as f32 / scal_values[4] as f32;
-            let days_since_2000 = days_since_2000 as f32 / scal_values[5] as f32;
-            let seconds_since_midnight = seconds_since_midnight as f32 / scal_values[6] as f32;
-            let dop = dop as f32 / scal_values[7] as f32;
-            let fix = (fix as f32 / scal_values[8] as f32) as u32;
-
-            Gps9 {
-                fix,
-                dop,
-                latitude,
-                longitude,
-                altitude,
-                speed_2d,
-                speed_3d,
-                days_since_2000,
-                seconds_since_midnight,
-            }
-        };
-
-        let accl = extract_vec3(child_klvs, "ACCL").unwrap_or_default();
-        let gyro = extract_vec3(child_klvs, "GYRO").unwrap_or_default();
-        let grav = extract_vec3(child_klvs, "GRAV").unwrap_or_default();
-        let cori = extract_quat(child_klvs, "CORI").unwrap_or_default();
-        let iori = extract_quat(child_klvs, "IORI").unwrap_or_default();
-
-        GpmfSample {
-            klvs: child_klvs.clone(),
-            gps9,
-            accl,
-            gyro,
-            grav,
-            cori,
-            iori,
-        }
+        #[cfg(feature = "std")]
+        let parser = GpmfSampleParser::builtin();
+        #[cfg(not(feature = "std"))]
+        let parser = &GpmfSampleParser::default();
+        parser.parse(devc_klv)
+    }
+}
+
+/// Registers the decoders [`GpmfSampleParser::default`] starts with.
+fn register_builtin_decoders(parser: &mut GpmfSampleParser) {
+    parser.register(Fourcc(*b"GPS9"), decode_gps9);
+    for fourcc in [*b"ACCL", *b"GYRO", *b"GRAV", *b"MAGN"] {
+        parser.register(Fourcc(fourcc), decode_vec3);
+    }
+    for fourcc in [*b"CORI", *b"IORI"] {
+        parser.register(Fourcc(fourcc), decode_quat);
+    }
+    for fourcc in [*b"SHUT", *b"ISOE", *b"ISOG", *b"WBAL", *b"YAVG", *b"UNIF", *b"MSKP", *b"LSKP"] {
+        parser.register(Fourcc(fourcc), decode_scalars);
     }
+    parser.register(Fourcc(*b"WRGB"), imaging::decode_rgb);
+    parser.register(Fourcc(*b"TMPC"), thermal::decode_tmpc);
+    parser.register(Fourcc(*b"WNDM"), audio::decode_wind);
+    parser.register(Fourcc(*b"MWET"), audio::decode_mic_wet);
+    parser.register(Fourcc(*b"AALP"), audio::decode_audio_level);
+    parser.register(Fourcc(*b"FACE"), face::decode_faces);
+    parser.register(Fourcc(*b"SCEN"), scene::decode_scen);
+    parser.register(Fourcc(*b"HUES"), scene::decode_hues);
 }
 
-/// Reads SCAL values as `f32` regardless of underlying numeric type. Returns
-/// `None` if the SCAL value isn't one of the numeric types GPMF actually uses.
-fn scal_as_f32(value: &Value) -> Option<Vec<f32>> {
+/// Decodes a `GPS9` stream into a [`Gps9`], using its first entry.
+fn decode_gps9(stream: &StreamContext<'_>) -> Option<DecodedStream> {
+    let Value::Complex(complex_value) = stream.data().value() else {
+        return None;
+    };
+
+    let Some(Value::Ascii(type_str)) = stream.sticky("TYPE").map(Klv::value) else {
+        return None;
+    };
+    if type_str != "lllllllSS" {
+        return None;
+    }
+
+    let Some(Value::S32(scal_values)) = stream.sticky("SCAL").map(Klv::value) else {
+        return None;
+    };
+    if scal_values.len() != 9 {
+        return None;
+    }
+
+    let mut reader = ByteReader::new(complex_value.raw_data());
+
+    let latitude = reader.read_i32().ok()?;
+    let longitude = reader.read_i32().ok()?;
+    let altitude = reader.read_i32().ok()?;
+    let speed_2d = reader.read_i32().ok()?;
+    let speed_3d = reader.read_i32().ok()?;
+    let days_since_2000 = reader.read_i32().ok()?;
+    let seconds_since_midnight = reader.read_i32().ok()?;
+    let dop = reader.read_u16().ok()?;
+    let fix = reader.read_u16().ok()?;
+
+    let latitude = latitude as f32 / scal_values[0] as f32;
+    let longitude = longitude as f32 / scal_values[1] as f32;
+    let altitude = altitude as f32 / scal_values[2] as f32;
+    let speed_2d = speed_2d as f32 / scal_values[3] as f32;
+    let speed_3d = speed_3d as f32 / scal_values[4] as f32;
+    let days_since_2000 = days_since_2000 as f32 / scal_values[5] as f32;
+    let seconds_since_midnight = seconds_since_midnight as f32 / scal_values[6] as f32;
+    let dop = dop as f32 / scal_values[7] as f32;
+    let fix = (fix as f32 / scal_values[8] as f32) as u32;
+
+    Some(DecodedStream::new(Gps9 {
+        fix,
+        dop,
+        latitude,
+        longitude,
+        altitude,
+        speed_2d,
+        speed_3d,
+        days_since_2000,
+        seconds_since_midnight,
+    }))
+}
+
+/// Decodes a stream of single numbers (`SHUT`, `WBAL`, ...) into a `Vec<f32>`.
+fn decode_scalars(stream: &StreamContext<'_>) -> Option<DecodedStream> {
+    let tuples = extract_axes(stream, 1)?;
+    let scalars: Vec<f32> = tuples.into_iter().map(|t| t[0]).collect();
+    Some(DecodedStream::new(scalars))
+}
+
+/// Decodes an `ACCL`-style stream into a `Vec<Vec3>`.
+fn decode_vec3(stream: &StreamContext<'_>) -> Option<DecodedStream> {
+    let tuples = extract_s16_axes(stream, 3)?;
+    let vec3s: Vec<Vec3> = tuples.into_iter().map(|t| Vec3 { x: t[0], y: t[1], z: t[2] }).collect();
+    Some(DecodedStream::new(vec3s))
+}
+
+/// Decodes a `CORI`-style stream into a `Vec<Quat>`.
+fn decode_quat(stream: &StreamContext<'_>) -> Option<DecodedStream> {
+    let tuples = extract_s16_axes(stream, 4)?;
+    let quats: Vec<Quat> = tuples.into_iter().map(|t| Quat { w: t[0], x: t[1], y: t[2], z: t[3] }).collect();
+    Some(DecodedStream::new(quats))
+}
+
+/// Reads numeric values (data or SCAL) as `f32` regardless of underlying
+/// type. Returns `None` if the value isn't one of the numeric types GPMF
+/// actually uses.
+fn numbers_as_f32(value: &Value) -> Option<Vec<f32>> {
     match value {
+        Value::S8(v) => Some(v.iter().map(|&x| x as f32).collect()),
+        Value::U8(v) => Some(v.iter().map(|&x| x as f32).collect()),
         Value::S16(v) => Some(v.iter().map(|&x| x as f32).collect()),
         Value::U16(v) => Some(v.iter().map(|&x| x as f32).collect()),
         Value::S32(v) => Some(v.iter().map(|&x| x as f32).collect()),
@@ -262,41 +318,29 @@ fn scal_for_axis(scal: &[f32], i: usize) -> f32 {
     if scal.len() == 1 { scal[0] } else { scal[i] }
 }
 
-/// Finds a STRM block in `child_klvs` whose nested children contain a KLV
-/// with the given `fourcc`. Returns the STRM's child KLV list.
-fn find_strm_for<'a>(child_klvs: &'a [Klv], fourcc: &str) -> Option<&'a [Klv]> {
-    for klv in child_klvs {
-        if klv.header().fourcc().as_str() != "STRM" {
-            continue;
-        }
-        let Value::Nested(strm_children) = klv.value() else { continue };
-        if strm_children.iter().any(|k| k.header().fourcc().as_str() == fourcc) {
-            return Some(strm_children);
-        }
+/// Generic extractor for STRM blocks whose data KLV is an `S16` array of
+/// `axis_count`-tuples, scaled per-axis by the sticky SCAL. Returns `None`
+/// when the stream doesn't match the expected shape.
+fn extract_s16_axes(stream: &StreamContext<'_>, axis_count: usize) -> Option<Vec<Vec<f32>>> {
+    if !matches!(stream.data().value(), Value::S16(_)) || stream.sticky("SCAL").is_none() {
+        return None;
     }
-    None
+    extract_axes(stream, axis_count)
 }
 
-/// Generic extractor for STRM blocks whose data KLV is an `S16` array of
-/// `axis_count`-tuples, scaled per-axis by the sibling SCAL. Returns `None`
-/// when the stream isn't present or doesn't match the expected shape.
-fn extract_s16_axes(
-    child_klvs: &[Klv],
-    fourcc: &str,
-    axis_count: usize,
-) -> Option<Vec<Vec<f32>>> {
-    let strm_children = find_strm_for(child_klvs, fourcc)?;
-
-    let data_klv = strm_children.iter().find(|k| k.header().fourcc().as_str() == fourcc)?;
-    let Value::S16(raw) = data_klv.value() else { return None };
+/// Generic extractor for STRM blocks whose data KLV is a numeric array of
+/// `axis_count`-tuples, scaled per-axis by the sticky SCAL if there is one.
+/// Returns `None` when the stream doesn't match the expected shape.
+fn extract_axes(stream: &StreamContext<'_>, axis_count: usize) -> Option<Vec<Vec<f32>>> {
+    let raw = numbers_as_f32(stream.data().value())?;
     if raw.len() % axis_count != 0 {
         return None;
     }
 
-    let scal = strm_children
-        .iter()
-        .find(|k| k.header().fourcc().as_str() == "SCAL")
-        .and_then(|k| scal_as_f32(k.value()))?;
+    let scal = match stream.sticky("SCAL") {
+        Some(scal_klv) => numbers_as_f32(scal_klv.value())?,
+        None => alloc::vec![1.0],
+    };
     if scal.len() != 1 && scal.len() != axis_count {
         return None;
     }
@@ -306,7 +350,7 @@ fn extract_s16_axes(
     for s in 0..sample_count {
         let mut tuple: Vec<f32> = Vec::with_capacity(axis_count);
         for a in 0..axis_count {
-            let v = raw[s * axis_count + a] as f32 / scal_for_axis(&scal, a);
+            let v = raw[s * axis_count + a] / scal_for_axis(&scal, a);
             tuple.push(v);
         }
         out.push(tuple);
@@ -314,27 +358,48 @@ fn extract_s16_axes(
     Some(out)
 }
 
-fn extract_vec3(child_klvs: &[Klv], fourcc: &str) -> Option<Vec<Vec3>> {
-    let tuples = extract_s16_axes(child_klvs, fourcc, 3)?;
-    Some(tuples.into_iter().map(|t| Vec3 { x: t[0], y: t[1], z: t[2] }).collect())
-}
-
-fn extract_quat(child_klvs: &[Klv], fourcc: &str) -> Option<Vec<Quat>> {
-    let tuples = extract_s16_axes(child_klvs, fourcc, 4)?;
-    Some(tuples.into_iter().map(|t| Quat { w: t[0], x: t[1], y: t[2], z: t[3] }).collect())
-}
-
-#[cfg(all(test, feature = "with-fixtures"))]
+#[cfg(test)]
 mod tests {
     use super::*;
-    use std::io::Cursor;
+
+    use synth::DevcBuilder;
 
     fn parse_sample(bytes: &[u8]) -> GpmfSample {
-        let klvs = Klv::from_reader(&mut Cursor::new(bytes)).unwrap();
+        let klvs = Klv::from_bytes(bytes).unwrap();
         let devc = klvs.iter().find(|k| k.header().fourcc().as_str() == "DEVC").unwrap();
         GpmfSample::new(devc)
     }
 
+    #[test]
+    fn builtin_streams_are_decoded_from_synthetic_sample() {
+        let sample = parse_sample(&DevcBuilder::typical(3).build());
+
+        let gps9 = sample.gps9();
+        assert_eq!(gps9.fix, 3);
+        assert!((gps9.latitude - 35.0003).abs() < 1e-5);
+        assert!((gps9.longitude - 139.0).abs() < 1e-5);
+        assert!((gps9.dop - 1.5).abs() < 1e-5);
+        assert!((gps9.seconds_since_midnight - 3603.0).abs() < 1e-3);
+
+        assert_eq!(sample.accl().len(), 200);
+        assert!((sample.accl()[0].x - 9.81).abs() < 1.0 / synth::ACCL_SCAL as f32);
+        assert_eq!(sample.gyro().len(), 200);
+        assert_eq!(sample.grav().len(), 60);
+        assert_eq!(sample.cori().len(), 60);
+        assert_eq!(sample.iori().len(), 60);
+        assert!((sample.cori()[0].w - 1.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn sample_without_gps9_reports_no_fix() {
+        let sample = parse_sample(&DevcBuilder::new(1, "Camera").accl(&synth::accl_at_rest(10)).build());
+
+        assert_eq!(sample.gps9().fix, 0);
+        assert_eq!(sample.accl().len(), 10);
+        assert!(sample.cori().is_empty());
+    }
+
+    #[cfg(feature = "with-fixtures")]
     #[test]
     fn imu_extraction_from_sample_60() {
         let bytes = include_bytes!("../test_files/sample_60.bin");
@@ -0,0 +1,100 @@
+use crate::klv::KlvError;
+
+/// Big-endian cursor over an in-memory GPMF payload.
+///
+/// Replaces `std::io::Read`/`Seek` so that parsing works under `no_std`.
+/// Offsets reported by [`ByteReader::position`] are absolute within the slice
+/// the root reader was created from, including for sub-readers.
+#[derive(Debug, Clone)]
+pub struct ByteReader<'a> {
+    data: &'a [u8],
+    position: usize,
+    end: usize,
+}
+
+impl<'a> ByteReader<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Self {
+            data,
+            position: 0,
+            end: data.len(),
+        }
+    }
+
+    /// Absolute offset of the next byte to be read.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.end - self.position
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.remaining() == 0
+    }
+
+    /// Reads exactly `len` bytes, borrowing them from the underlying slice.
+    pub fn read_bytes(&mut self, len: usize) -> Result<&'a [u8], KlvError> {
+        if self.remaining() < len {
+            return Err(KlvError::UnexpectedEof(self.position));
+        }
+        let bytes = &self.data[self.position..self.position + len];
+        self.position += len;
+        Ok(bytes)
+    }
+
+    pub fn read_array<const N: usize>(&mut self) -> Result<[u8; N], KlvError> {
+        let mut array = [0; N];
+        array.copy_from_slice(self.read_bytes(N)?);
+        Ok(array)
+    }
+
+    pub fn skip(&mut self, len: usize) -> Result<(), KlvError> {
+        self.read_bytes(len).map(|_| ())
+    }
+
+    /// Splits off the next `len` bytes as a reader of their own, advancing
+    /// `self` past them.
+    pub fn sub_reader(&mut self, len: usize) -> Result<Self, KlvError> {
+        let start = self.position;
+        self.skip(len)?;
+        Ok(Self {
+            data: self.data,
+            position: start,
+            end: start + len,
+        })
+    }
+
+    pub fn read_u8(&mut self) -> Result<u8, KlvError> {
+        Ok(self.read_array::<1>()?[0])
+    }
+
+    pub fn read_i8(&mut self) -> Result<i8, KlvError> {
+        Ok(self.read_u8()? as i8)
+    }
+
+    pub fn read_u16(&mut self) -> Result<u16, KlvError> {
+        Ok(u16::from_be_bytes(self.read_array()?))
+    }
+
+    pub fn read_i16(&mut self) -> Result<i16, KlvError> {
+        Ok(i16::from_be_bytes(self.read_array()?))
+    }
+
+    pub fn read_u32(&mut self) -> Result<u32, KlvError> {
+        Ok(u32::from_be_bytes(self.read_array()?))
+    }
+
+    pub fn read_i32(&mut self) -> Result<i32, KlvError> {
+        Ok(i32::from_be_bytes(self.read_array()?))
+    }
+
+    pub fn read_u64(&mut self) -> Result<u64, KlvError> {
+        Ok(u64::from_be_bytes(self.read_array()?))
+    }
+
+    pub fn read_f32(&mut self) -> Result<f32, KlvError> {
+        Ok(f32::from_be_bytes(self.read_array()?))
+    }
+}
@@ -0,0 +1,205 @@
+//! `SCEN` scene classification and `HUES` dominant hue streams.
+//!
+//! Both store one KLV per video frame: `SCEN` as `[classifier, probability]`
+//! structs (`TYPE` `Ff`), `HUES` as `[hue, weight]` structs (`TYPE` `BB`).
+
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+use crate::decode::{DecodedStream, StreamContext};
+use crate::klv::{Fourcc, Value};
+use crate::GpmfSample;
+
+/// Classes the `SCEN` classifier reports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum SceneClass {
+    Snow,
+    Urban,
+    Indoor,
+    Water,
+    Vegetation,
+    Beach,
+    /// A classifier this crate doesn't know about.
+    Other(Fourcc),
+}
+
+impl From<Fourcc> for SceneClass {
+    fn from(fourcc: Fourcc) -> Self {
+        match fourcc.as_bytes() {
+            b"SNOW" => Self::Snow,
+            b"URBA" => Self::Urban,
+            b"INDO" => Self::Indoor,
+            b"WATR" => Self::Water,
+            b"VEGE" => Self::Vegetation,
+            b"BEAC" => Self::Beach,
+            _ => Self::Other(fourcc),
+        }
+    }
+}
+
+/// Probability of one [`SceneClass`] in a frame, in `[0, 1]`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SceneProbability {
+    pub class: SceneClass,
+    pub probability: f32,
+}
+
+/// One of the dominant hues of a frame.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Hue {
+    /// In degrees, in `[0, 360]`.
+    pub hue: f32,
+    /// Share of the frame, in `[0, 1]`.
+    pub weight: f32,
+}
+
+impl GpmfSample {
+    /// Scene class probabilities of each video frame.
+    /// Empty slice when the source has no `SCEN` stream.
+    pub fn scen(&self) -> &[Vec<SceneProbability>] {
+        self.decoded_as::<Vec<Vec<SceneProbability>>>(Fourcc(*b"SCEN")).map_or(&[], Vec::as_slice)
+    }
+
+    /// Dominant hues of each video frame.
+    /// Empty slice when the source has no `HUES` stream.
+    pub fn hues(&self) -> &[Vec<Hue>] {
+        self.decoded_as::<Vec<Vec<Hue>>>(Fourcc(*b"HUES")).map_or(&[], Vec::as_slice)
+    }
+}
+
+/// Number of frames in which each [`SceneClass`] was the most probable one,
+/// accumulated over any number of samples.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SceneSummary {
+    frame_count: usize,
+    dominant_counts: BTreeMap<SceneClass, usize>,
+}
+
+impl SceneSummary {
+    pub fn add_sample(&mut self, sample: &GpmfSample) {
+        for frame in sample.scen() {
+            self.frame_count += 1;
+            let dominant = frame.iter().max_by(|a, b| a.probability.total_cmp(&b.probability));
+            if let Some(dominant) = dominant {
+                *self.dominant_counts.entry(dominant.class).or_default() += 1;
+            }
+        }
+    }
+
+    /// Number of frames with a `SCEN` entry.
+    pub fn frame_count(&self) -> usize {
+        self.frame_count
+    }
+
+    /// Number of frames classified as `class`.
+    pub fn dominant_count(&self, class: SceneClass) -> usize {
+        self.dominant_counts.get(&class).copied().unwrap_or(0)
+    }
+
+    /// Fraction of frames classified as `class`, in `[0, 1]`.
+    /// `0` when there are no frames.
+    pub fn fraction(&self, class: SceneClass) -> f32 {
+        if self.frame_count == 0 {
+            return 0.0;
+        }
+        self.dominant_count(class) as f32 / self.frame_count as f32
+    }
+
+    /// Classes that were dominant in at least one frame, most frequent first.
+    pub fn classes(&self) -> Vec<(SceneClass, usize)> {
+        let mut classes: Vec<_> = self.dominant_counts.iter().map(|(&class, &count)| (class, count)).collect();
+        classes.sort_by_key(|&(_, count)| core::cmp::Reverse(count));
+        classes
+    }
+}
+
+/// Decodes every KLV of a per-frame struct stream with `decode_struct`.
+fn decode_frames<T>(stream: &StreamContext<'_>, decode_struct: impl Fn(&[f64]) -> Option<T>) -> Option<Vec<Vec<T>>> {
+    let Value::Ascii(type_str) = stream.sticky("TYPE")?.value() else {
+        return None;
+    };
+    stream
+        .data_klvs()
+        .map(|klv| {
+            let Value::Complex(value) = klv.value() else {
+                return None;
+            };
+            value.structs(type_str).ok()?.iter().map(|fields| decode_struct(fields)).collect()
+        })
+        .collect()
+}
+
+/// Decodes a `SCEN` stream into a `Vec<Vec<SceneProbability>>`.
+pub(crate) fn decode_scen(stream: &StreamContext<'_>) -> Option<DecodedStream> {
+    let frames = decode_frames(stream, |fields| match *fields {
+        [class, probability] => Some(SceneProbability {
+            class: Fourcc((class as u32).to_be_bytes()).into(),
+            probability: probability as f32,
+        }),
+        _ => None,
+    })?;
+    Some(DecodedStream::new(frames))
+}
+
+/// Decodes a `HUES` stream into a `Vec<Vec<Hue>>`.
+pub(crate) fn decode_hues(stream: &StreamContext<'_>) -> Option<DecodedStream> {
+    let frames = decode_frames(stream, |fields| match *fields {
+        [hue, weight] => Some(Hue {
+            hue: (hue * 360.0 / 255.0) as f32,
+            weight: (weight / 255.0) as f32,
+        }),
+        _ => None,
+    })?;
+    Some(DecodedStream::new(frames))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use alloc::vec;
+
+    use crate::synth::DevcBuilder;
+    use crate::Klv;
+
+    fn parse(bytes: &[u8]) -> GpmfSample {
+        let klvs = Klv::from_bytes(bytes).unwrap();
+        GpmfSample::new(&klvs[0])
+    }
+
+    fn scene(class: SceneClass, probability: f32) -> SceneProbability {
+        SceneProbability { class, probability }
+    }
+
+    #[test]
+    fn scen_and_hues_are_decoded_per_frame() {
+        let scen = vec![
+            vec![scene(SceneClass::Water, 0.75), scene(SceneClass::Beach, 0.25)],
+            vec![scene(SceneClass::Other(Fourcc(*b"SKYY")), 1.0)],
+        ];
+        let hues = vec![vec![Hue { hue: 180.0, weight: 0.6 }, Hue { hue: 45.0, weight: 0.4 }], vec![]];
+        let sample = parse(&DevcBuilder::new(1, "Camera").scen(&scen).hues(&hues).build());
+
+        assert_eq!(sample.scen(), scen);
+        assert_eq!(sample.hues().len(), 2);
+        assert!(sample.hues()[1].is_empty());
+        for (decoded, expected) in sample.hues()[0].iter().zip(&hues[0]) {
+            assert!((decoded.hue - expected.hue).abs() < 1.0, "{decoded:?}");
+            assert!((decoded.weight - expected.weight).abs() < 0.01, "{decoded:?}");
+        }
+    }
+
+    #[test]
+    fn summary_counts_dominant_class_per_frame() {
+        let water = vec![scene(SceneClass::Water, 0.9), scene(SceneClass::Beach, 0.1)];
+        let beach = vec![scene(SceneClass::Water, 0.4), scene(SceneClass::Beach, 0.6)];
+        let mut summary = SceneSummary::default();
+        summary.add_sample(&parse(&DevcBuilder::new(1, "Camera").scen(&[water.clone(), water.clone(), beach]).build()));
+        summary.add_sample(&parse(&DevcBuilder::new(1, "Camera").scen(&[water]).build()));
+
+        assert_eq!(summary.frame_count(), 4);
+        assert_eq!(summary.fraction(SceneClass::Water), 0.75);
+        assert_eq!(summary.fraction(SceneClass::Snow), 0.0);
+        assert_eq!(summary.classes(), [(SceneClass::Water, 3), (SceneClass::Beach, 1)]);
+    }
+}
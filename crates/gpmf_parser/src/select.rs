@@ -0,0 +1,356 @@
+//! Path selectors over a KLV tree.
+//!
+//! A selector is a `/`-separated list of steps, matched from the top-level
+//! KLVs downwards:
+//!
+//! ```text
+//! DEVC[DVID=1]/STRM[has GPS9]/SCAL
+//! ```
+//!
+//! - A step is a FourCC (names shorter than 4 characters are space-padded),
+//!   `*` for any single KLV, or `**` for any number of levels (including none).
+//!   A trailing `**` selects every descendant.
+//! - `[has XXXX]` requires a nested KLV to have a child `XXXX`.
+//! - `[XXXX=value]` / `[XXXX!=value]` compare the value of a child `XXXX`.
+//!   Numbers match single-element numeric values, anything else is compared
+//!   against `Ascii` and `Fourcc` values. Values may be quoted with `"`.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use thiserror::Error;
+
+use crate::klv::{Fourcc, Klv, Value};
+
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum SelectorError {
+    #[error("Empty selector step at offset {0}")]
+    EmptyStep(usize),
+    #[error("Invalid FourCC '{1}' at offset {0}")]
+    InvalidFourcc(usize, String),
+    #[error("Invalid predicate at offset {0}")]
+    InvalidPredicate(usize),
+    #[error("Unexpected character '{1}' at offset {0}")]
+    UnexpectedChar(usize, char),
+    #[error("Unterminated predicate or string starting at offset {0}")]
+    Unterminated(usize),
+}
+
+/// A compiled selector. See the [module documentation](self) for the syntax.
+#[derive(Debug, Clone)]
+pub struct Selector {
+    steps: Vec<Step>,
+}
+
+#[derive(Debug, Clone)]
+enum Step {
+    Match {
+        fourcc: Option<Fourcc>,
+        predicates: Vec<Predicate>,
+    },
+    AnyDepth,
+}
+
+#[derive(Debug, Clone)]
+enum Predicate {
+    Has(Fourcc),
+    Equals(Fourcc, Literal),
+    NotEquals(Fourcc, Literal),
+}
+
+#[derive(Debug, Clone)]
+enum Literal {
+    Number(f64),
+    Text(String),
+}
+
+impl Selector {
+    pub fn parse(selector: &str) -> Result<Self, SelectorError> {
+        let mut steps: Vec<Step> = Vec::new();
+
+        let mut offset = 0;
+        for step_str in selector.split('/') {
+            steps.push(parse_step(step_str, offset)?);
+            offset += step_str.len() + 1;
+        }
+
+        Ok(Self { steps })
+    }
+
+    /// Returns every KLV matched by the selector. Matches closer to the root
+    /// come first when `**` is involved, otherwise they are in tree order.
+    pub fn select<'a>(&self, klvs: &'a [Klv]) -> Vec<&'a Klv> {
+        let mut matches: Vec<&'a Klv> = Vec::new();
+        select_steps(klvs, &self.steps, &mut matches);
+        matches
+    }
+
+    /// Returns the first of the KLVs [`Selector::select`] would return.
+    pub fn select_first<'a>(&self, klvs: &'a [Klv]) -> Option<&'a Klv> {
+        self.select(klvs).into_iter().next()
+    }
+}
+
+impl core::str::FromStr for Selector {
+    type Err = SelectorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+fn parse_step(step_str: &str, offset: usize) -> Result<Step, SelectorError> {
+    let name_end = step_str.find('[').unwrap_or(step_str.len());
+    let name = step_str[..name_end].trim();
+    if name.is_empty() {
+        return Err(SelectorError::EmptyStep(offset));
+    }
+
+    let fourcc = match name {
+        "**" => {
+            if name_end != step_str.len() {
+                return Err(SelectorError::UnexpectedChar(offset + name_end, '['));
+            }
+            return Ok(Step::AnyDepth);
+        }
+        "*" => None,
+        _ => Some(parse_fourcc(name, offset)?),
+    };
+
+    let mut predicates: Vec<Predicate> = Vec::new();
+    let mut rest = &step_str[name_end..];
+    let mut rest_offset = offset + name_end;
+    while !rest.is_empty() {
+        if !rest.starts_with('[') {
+            let c = rest.chars().next().unwrap();
+            return Err(SelectorError::UnexpectedChar(rest_offset, c));
+        }
+        let close = find_predicate_end(rest).ok_or(SelectorError::Unterminated(rest_offset))?;
+        predicates.push(parse_predicate(&rest[1..close], rest_offset + 1)?);
+
+        rest = &rest[close + 1..];
+        rest_offset += close + 1;
+    }
+
+    Ok(Step::Match { fourcc, predicates })
+}
+
+/// Finds the `]` closing the predicate at the start of `s`, skipping quoted text.
+fn find_predicate_end(s: &str) -> Option<usize> {
+    let mut in_quotes = false;
+    for (i, c) in s.char_indices() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            ']' if !in_quotes => return Some(i),
+            _ => {}
+        }
+    }
+    None
+}
+
+fn parse_predicate(predicate: &str, offset: usize) -> Result<Predicate, SelectorError> {
+    let trimmed = predicate.trim();
+
+    if let Some(fourcc) = trimmed.strip_prefix("has ") {
+        return Ok(Predicate::Has(parse_fourcc(fourcc.trim(), offset)?));
+    }
+
+    let (fourcc, literal, negated) = if let Some((fourcc, literal)) = trimmed.split_once("!=") {
+        (fourcc, literal, true)
+    } else if let Some((fourcc, literal)) = trimmed.split_once('=') {
+        (fourcc, literal, false)
+    } else {
+        return Err(SelectorError::InvalidPredicate(offset));
+    };
+
+    let fourcc = parse_fourcc(fourcc.trim(), offset)?;
+    let literal = parse_literal(literal.trim(), offset)?;
+
+    Ok(if negated {
+        Predicate::NotEquals(fourcc, literal)
+    } else {
+        Predicate::Equals(fourcc, literal)
+    })
+}
+
+fn parse_fourcc(name: &str, offset: usize) -> Result<Fourcc, SelectorError> {
+    if name.is_empty() || name.len() > 4 || !name.bytes().all(|b| b.is_ascii_alphanumeric()) {
+        return Err(SelectorError::InvalidFourcc(offset, name.into()));
+    }
+
+    let mut bytes = [b' '; 4];
+    bytes[..name.len()].copy_from_slice(name.as_bytes());
+    Ok(Fourcc(bytes))
+}
+
+fn parse_literal(literal: &str, offset: usize) -> Result<Literal, SelectorError> {
+    if let Some(quoted) = literal.strip_prefix('"') {
+        let text = quoted.strip_suffix('"').ok_or(SelectorError::Unterminated(offset))?;
+        return Ok(Literal::Text(text.into()));
+    }
+    if literal.is_empty() {
+        return Err(SelectorError::InvalidPredicate(offset));
+    }
+
+    Ok(match literal.parse::<f64>() {
+        Ok(number) => Literal::Number(number),
+        Err(_) => Literal::Text(literal.into()),
+    })
+}
+
+fn select_steps<'a>(klvs: &'a [Klv], steps: &[Step], matches: &mut Vec<&'a Klv>) {
+    let Some((step, rest)) = steps.split_first() else {
+        return;
+    };
+
+    match step {
+        Step::AnyDepth => {
+            if rest.is_empty() {
+                // A trailing `**` selects every descendant.
+                for klv in klvs {
+                    push_unique(matches, klv);
+                    if let Value::Nested(children) = klv.value() {
+                        select_steps(children, steps, matches);
+                    }
+                }
+                return;
+            }
+
+            select_steps(klvs, rest, matches);
+            for klv in klvs {
+                if let Value::Nested(children) = klv.value() {
+                    select_steps(children, steps, matches);
+                }
+            }
+        }
+        Step::Match { fourcc, predicates } => {
+            for klv in klvs {
+                if fourcc.is_some_and(|fourcc| klv.header().fourcc() != fourcc) {
+                    continue;
+                }
+                if !predicates.iter().all(|predicate| predicate.matches(klv)) {
+                    continue;
+                }
+
+                if rest.is_empty() {
+                    push_unique(matches, klv);
+                } else if let Value::Nested(children) = klv.value() {
+                    select_steps(children, rest, matches);
+                }
+            }
+        }
+    }
+}
+
+fn push_unique<'a>(matches: &mut Vec<&'a Klv>, klv: &'a Klv) {
+    if !matches.iter().any(|m| core::ptr::eq(*m, klv)) {
+        matches.push(klv);
+    }
+}
+
+impl Predicate {
+    fn matches(&self, klv: &Klv) -> bool {
+        let Value::Nested(children) = klv.value() else {
+            return false;
+        };
+        let find = |fourcc: &Fourcc| children.iter().find(|child| child.header().fourcc() == *fourcc);
+
+        match self {
+            Self::Has(fourcc) => find(fourcc).is_some(),
+            Self::Equals(fourcc, literal) => find(fourcc).is_some_and(|child| literal.matches(child.value())),
+            Self::NotEquals(fourcc, literal) => !find(fourcc).is_some_and(|child| literal.matches(child.value())),
+        }
+    }
+}
+
+impl Literal {
+    fn matches(&self, value: &Value) -> bool {
+        match self {
+            Self::Number(number) => single_number(value).is_some_and(|v| v == *number),
+            Self::Text(text) => match value {
+                Value::Ascii(s) => s.trim_end_matches('\0') == text,
+                Value::Fourcc(v) => v.len() == 1 && v[0].as_str().trim_end() == text.trim_end(),
+                _ => false,
+            },
+        }
+    }
+}
+
+fn single_number(value: &Value) -> Option<f64> {
+    fn single<T: Copy + Into<f64>>(v: &[T]) -> Option<f64> {
+        match v {
+            [x] => Some((*x).into()),
+            _ => None,
+        }
+    }
+
+    match value {
+        Value::S8(v) => single(v),
+        Value::U8(v) => single(v),
+        Value::S16(v) => single(v),
+        Value::U16(v) => single(v),
+        Value::S32(v) => single(v),
+        Value::U32(v) => single(v),
+        Value::F32(v) => single(v),
+        Value::U64(v) => match v.as_slice() {
+            [x] => Some(*x as f64),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[rustfmt::skip]
+    const TWO_DEVICES: &[u8] = &[
+        b'D', b'E', b'V', b'C', 0, 1, 0, 44,
+            b'D', b'V', b'I', b'D', b'L', 4, 0, 1, 0, 0, 0, 1,
+            b'S', b'T', b'R', b'M', 0, 1, 0, 24,
+                b'S', b'C', b'A', b'L', b's', 2, 0, 1, 0, 10, 0, 0,
+                b'G', b'P', b'S', b'9', b'?', 4, 0, 1, 0, 0, 0, 0,
+        b'D', b'E', b'V', b'C', 0, 1, 0, 44,
+            b'D', b'V', b'I', b'D', b'L', 4, 0, 1, 0, 0, 0, 2,
+            b'S', b'T', b'R', b'M', 0, 1, 0, 24,
+                b'S', b'C', b'A', b'L', b's', 2, 0, 1, 0, 20, 0, 0,
+                b'G', b'P', b'S', b'9', b'?', 4, 0, 1, 0, 0, 0, 0,
+    ];
+
+    fn scal_of(klv: &Klv) -> i16 {
+        let Value::S16(v) = klv.value() else { panic!("S16 SCAL expected") };
+        v[0]
+    }
+
+    #[test]
+    fn selects_by_predicates() {
+        let klvs = Klv::from_bytes(TWO_DEVICES).unwrap();
+
+        let selector = Selector::parse("DEVC[DVID=2]/STRM[has GPS9]/SCAL").unwrap();
+        let matches = selector.select(&klvs);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(scal_of(matches[0]), 20);
+
+        let matches = Selector::parse("DEVC[DVID!=2]/*/SCAL").unwrap().select(&klvs);
+        assert_eq!(matches.iter().map(|k| scal_of(k)).collect::<Vec<_>>(), [10]);
+
+        assert!(Selector::parse("DEVC/STRM[has ACCL]").unwrap().select(&klvs).is_empty());
+    }
+
+    #[test]
+    fn any_depth_step() {
+        let klvs = Klv::from_bytes(TWO_DEVICES).unwrap();
+
+        assert_eq!(Selector::parse("**/SCAL").unwrap().select(&klvs).len(), 2);
+        assert_eq!(Selector::parse("DEVC/**").unwrap().select(&klvs).len(), 8);
+    }
+
+    #[test]
+    fn rejects_malformed_selectors() {
+        assert_eq!(Selector::parse("DEVC//SCAL").unwrap_err(), SelectorError::EmptyStep(5));
+        assert!(matches!(Selector::parse("DEVICE"), Err(SelectorError::InvalidFourcc(0, _))));
+        assert!(matches!(Selector::parse("DEVC[DVID"), Err(SelectorError::Unterminated(4))));
+        assert!(matches!(Selector::parse("DEVC[DVID]"), Err(SelectorError::InvalidPredicate(5))));
+    }
+}
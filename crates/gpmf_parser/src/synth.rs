@@ -0,0 +1,590 @@
+//! Builders for synthetic GPMF payloads, for hermetic tests and fixtures.
+//!
+//! [`DevcBuilder`] produces the bytes of one `DEVC` KLV, i.e. what a GoPro
+//! writes as a single sample of its `GoPro MET` track. Each stream is written
+//! with the sticky metadata real cameras emit (`STNM`, `SIUN`, `TSMP`, `SCAL`
+//! and, for `GPS9`, `TYPE`), so the output exercises the same paths as
+//! recorded data:
+//!
+//! ```
+//! use gpmf_parser::synth::{self, DevcBuilder};
+//!
+//! let bytes = DevcBuilder::new(1, "Camera")
+//!     .gps9(&[synth::gps9_fix(35.0, 139.0)])
+//!     .accl(&synth::accl_at_rest(200))
+//!     .build();
+//! let klvs = gpmf_parser::Klv::from_bytes(&bytes).unwrap();
+//! let sample = gpmf_parser::GpmfSample::new(&klvs[0]);
+//! assert_eq!(sample.accl().len(), 200);
+//! ```
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::audio::{AudioLevel, MicWet, Wind};
+use crate::face::{Face, FaceLayout};
+use crate::imaging::Rgb;
+use crate::klv::ValueType;
+use crate::scene::{Hue, SceneClass, SceneProbability};
+use crate::{Gps9, Quat, Vec3};
+
+/// `SCAL` of a `GPS9` stream as written by a HERO11.
+pub const GPS9_SCAL: [i32; 9] = [10_000_000, 10_000_000, 1000, 1000, 100, 1, 1000, 100, 1];
+/// `SCAL` of a `GPS5` stream.
+pub const GPS5_SCAL: [i32; 5] = [10_000_000, 10_000_000, 1000, 1000, 100];
+pub const ACCL_SCAL: i16 = 418;
+pub const GYRO_SCAL: i16 = 939;
+pub const MAGN_SCAL: i16 = 1;
+/// `SCAL` of the unit-length `GRAV`, `CORI` and `IORI` streams.
+pub const UNIT_SCAL: i16 = 32767;
+
+/// Encodes one non-nested KLV, padding the value to a multiple of 4 bytes.
+///
+/// ## Panics
+/// - If `data.len()` isn't `sample_size * repeat`.
+pub fn klv(fourcc: &[u8; 4], value_type: u8, sample_size: u8, repeat: u16, data: &[u8]) -> Vec<u8> {
+    assert_eq!(data.len(), sample_size as usize * repeat as usize);
+
+    let mut bytes = fourcc.to_vec();
+    bytes.extend([value_type, sample_size]);
+    bytes.extend(repeat.to_be_bytes());
+    bytes.extend(data);
+    bytes.resize(bytes.len().next_multiple_of(4), 0);
+    bytes
+}
+
+/// Encodes a nested KLV whose children are already-encoded KLVs.
+pub fn nested(fourcc: &[u8; 4], children: &[Vec<u8>]) -> Vec<u8> {
+    let payload = children.concat();
+    let repeat = u16::try_from(payload.len()).expect("Nested payload exceeds 64 KiB.");
+    klv(fourcc, 0, 1, repeat, &payload)
+}
+
+/// Encodes a `c` KLV. Characters are written as Latin-1, which is what GoPro
+/// uses for e.g. `m/s²`.
+///
+/// ## Panics
+/// - If `text` contains characters outside Latin-1.
+pub fn ascii(fourcc: &[u8; 4], text: &str) -> Vec<u8> {
+    let latin1: Vec<u8> = text
+        .chars()
+        .map(|c| u8::try_from(u32::from(c)).expect("Only Latin-1 text can be encoded."))
+        .collect();
+    klv(fourcc, b'c', latin1.len() as u8, 1, &latin1)
+}
+
+pub fn s16s(fourcc: &[u8; 4], values: &[i16]) -> Vec<u8> {
+    let data: Vec<u8> = values.iter().flat_map(|v| v.to_be_bytes()).collect();
+    klv(fourcc, b's', 2, values.len() as u16, &data)
+}
+
+pub fn u16s(fourcc: &[u8; 4], values: &[u16]) -> Vec<u8> {
+    let data: Vec<u8> = values.iter().flat_map(|v| v.to_be_bytes()).collect();
+    klv(fourcc, b'S', 2, values.len() as u16, &data)
+}
+
+pub fn s32s(fourcc: &[u8; 4], values: &[i32]) -> Vec<u8> {
+    let data: Vec<u8> = values.iter().flat_map(|v| v.to_be_bytes()).collect();
+    klv(fourcc, b'l', 4, values.len() as u16, &data)
+}
+
+pub fn u32s(fourcc: &[u8; 4], values: &[u32]) -> Vec<u8> {
+    let data: Vec<u8> = values.iter().flat_map(|v| v.to_be_bytes()).collect();
+    klv(fourcc, b'L', 4, values.len() as u16, &data)
+}
+
+/// Encodes `values` as `f` KLV of `axis_count`-tuples.
+pub fn f32s(fourcc: &[u8; 4], axis_count: usize, values: &[f32]) -> Vec<u8> {
+    let data: Vec<u8> = values.iter().flat_map(|v| v.to_be_bytes()).collect();
+    klv(fourcc, b'f', (4 * axis_count) as u8, (values.len() / axis_count) as u16, &data)
+}
+
+/// `GPS5` value, as written by cameras before the HERO11.
+#[derive(Debug, Clone, Copy)]
+pub struct Gps5 {
+    pub latitude: f32,
+    pub longitude: f32,
+    /// Altitude in _m_.
+    pub altitude: f32,
+    /// 2D speed in _m/s_.
+    pub speed_2d: f32,
+    /// 3D speed in _m/s_.
+    pub speed_3d: f32,
+}
+
+/// A 3D fix with plausible values besides the given position.
+pub fn gps9_fix(latitude: f32, longitude: f32) -> Gps9 {
+    Gps9 {
+        fix: 3,
+        dop: 1.5,
+        latitude,
+        longitude,
+        altitude: 40.0,
+        speed_2d: 1.5,
+        speed_3d: 1.5,
+        days_since_2000: 9000.0,
+        seconds_since_midnight: 3600.0,
+    }
+}
+
+/// `ACCL` samples of a camera lying still, with gravity on the first raw axis.
+pub fn accl_at_rest(count: usize) -> Vec<Vec3> {
+    alloc::vec![Vec3 { x: 9.81, y: 0.0, z: 0.0 }; count]
+}
+
+/// Builds the children of one `STRM`.
+#[derive(Debug, Clone, Default)]
+pub struct StreamBuilder {
+    klvs: Vec<Vec<u8>>,
+}
+
+impl StreamBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `STNM`, the stream's display name.
+    pub fn name(self, name: &str) -> Self {
+        self.klv(ascii(b"STNM", name))
+    }
+
+    /// Adds `SIUN`, the stream's SI unit.
+    pub fn units(self, units: &str) -> Self {
+        self.klv(ascii(b"SIUN", units))
+    }
+
+    /// Adds `TSMP`, the total number of samples since recording started.
+    pub fn tsmp(self, total_samples: u32) -> Self {
+        self.klv(u32s(b"TSMP", &[total_samples]))
+    }
+
+    /// Adds an already-encoded KLV.
+    pub fn klv(mut self, klv: Vec<u8>) -> Self {
+        self.klvs.push(klv);
+        self
+    }
+
+    /// Encodes the `STRM` KLV.
+    pub fn build(&self) -> Vec<u8> {
+        nested(b"STRM", &self.klvs)
+    }
+}
+
+/// Builds the bytes of a `DEVC` KLV.
+#[derive(Debug, Clone)]
+pub struct DevcBuilder {
+    device_id: u32,
+    device_name: String,
+    sample_index: u32,
+    temperature: Option<f32>,
+    streams: Vec<Vec<u8>>,
+}
+
+impl DevcBuilder {
+    pub fn new(device_id: u32, device_name: &str) -> Self {
+        Self {
+            device_id,
+            device_name: device_name.into(),
+            sample_index: 0,
+            temperature: None,
+            streams: Vec::new(),
+        }
+    }
+
+    /// A `DEVC` shaped like one second of HERO11 output: 10 `GPS9` fixes and
+    /// 200 `ACCL`, 200 `GYRO`, 60 `GRAV`, 60 `CORI` and 60 `IORI` samples.
+    /// The position moves north with `sample_index`.
+    pub fn typical(sample_index: u32) -> Self {
+        let fixes: Vec<Gps9> = (0..10)
+            .map(|i| {
+                let mut fix = gps9_fix(35.0 + (sample_index * 10 + i) as f32 * 1e-5, 139.0);
+                fix.seconds_since_midnight += sample_index as f32 + i as f32 * 0.1;
+                fix
+            })
+            .collect();
+        let gyro = alloc::vec![Vec3 { x: 0.0, y: 0.1, z: 0.0 }; 200];
+        let grav = alloc::vec![Vec3 { x: 0.0, y: 1.0, z: 0.0 }; 60];
+        let identity = alloc::vec![Quat { w: 1.0, x: 0.0, y: 0.0, z: 0.0 }; 60];
+
+        Self::new(1, "Camera")
+            .sample_index(sample_index)
+            .accl(&accl_at_rest(200))
+            .gyro(&gyro)
+            .gps9(&fixes)
+            .grav(&grav)
+            .cori(&identity)
+            .iori(&identity)
+    }
+
+    /// Index of this payload within the track, used to derive `TSMP`
+    /// assuming each stream has the same number of samples every time.
+    pub fn sample_index(mut self, sample_index: u32) -> Self {
+        self.sample_index = sample_index;
+        self
+    }
+
+    /// Writes a `TMPC` of `celsius` into the `ACCL` and `GYRO` streams added
+    /// after this call, as the cameras do.
+    pub fn tmpc(mut self, celsius: f32) -> Self {
+        self.temperature = Some(celsius);
+        self
+    }
+
+    pub fn gps9(self, fixes: &[Gps9]) -> Self {
+        let data: Vec<u8> = fixes
+            .iter()
+            .flat_map(|gps9| {
+                let mut bytes = Vec::with_capacity(32);
+                for (value, scal) in [
+                    gps9.latitude,
+                    gps9.longitude,
+                    gps9.altitude,
+                    gps9.speed_2d,
+                    gps9.speed_3d,
+                    gps9.days_since_2000,
+                    gps9.seconds_since_midnight,
+                ]
+                .into_iter()
+                .zip(GPS9_SCAL)
+                {
+                    bytes.extend(scale_i32(value, scal).to_be_bytes());
+                }
+                bytes.extend((scale_i32(gps9.dop, GPS9_SCAL[7]) as u16).to_be_bytes());
+                bytes.extend((gps9.fix as u16).to_be_bytes());
+                bytes
+            })
+            .collect();
+
+        let strm = self
+            .stream_header("GPS (Lat., Long., Alt., 2D, 3D, days, secs, DOP, fix)", fixes.len())
+            .units("deg,deg,m,m/s,m/s,,s,,")
+            .klv(ascii(b"TYPE", "lllllllSS"))
+            .klv(s32s(b"SCAL", &GPS9_SCAL))
+            .klv(klv(b"GPS9", b'?', 32, fixes.len() as u16, &data));
+        self.stream(strm)
+    }
+
+    /// Adds a `GPS5` stream along with the `GPSF` fix and `GPSP` precision
+    /// that accompany it.
+    pub fn gps5(self, points: &[Gps5], fix: u32, dop: f32) -> Self {
+        let values: Vec<i32> = points
+            .iter()
+            .flat_map(|p| {
+                [p.latitude, p.longitude, p.altitude, p.speed_2d, p.speed_3d]
+                    .into_iter()
+                    .zip(GPS5_SCAL)
+                    .map(|(value, scal)| scale_i32(value, scal))
+            })
+            .collect();
+        let data: Vec<u8> = values.iter().flat_map(|v| v.to_be_bytes()).collect();
+
+        let strm = self
+            .stream_header("GPS (Lat., Long., Alt., 2D speed, 3D speed)", points.len())
+            .units("deg,deg,m,m/s,m/s")
+            .klv(u32s(b"GPSF", &[fix]))
+            .klv(klv(b"GPSP", b'S', 2, 1, &((dop * 100.0) as u16).to_be_bytes()))
+            .klv(s32s(b"SCAL", &GPS5_SCAL))
+            .klv(klv(b"GPS5", b'l', 20, points.len() as u16, &data));
+        self.stream(strm)
+    }
+
+    pub fn accl(self, samples: &[Vec3]) -> Self {
+        let strm = self.vec3_stream(b"ACCL", "Accelerometer", "m/s²", ACCL_SCAL, samples);
+        self.stream(strm)
+    }
+
+    pub fn gyro(self, samples: &[Vec3]) -> Self {
+        let strm = self.vec3_stream(b"GYRO", "Gyroscope", "rad/s", GYRO_SCAL, samples);
+        self.stream(strm)
+    }
+
+    /// Adds a `MAGN` stream. Samples are in raw `z, x, y` order.
+    pub fn magn(self, samples: &[Vec3]) -> Self {
+        let strm = self.vec3_stream(b"MAGN", "Magnetometer", "µT", MAGN_SCAL, samples);
+        self.stream(strm)
+    }
+
+    pub fn grav(self, samples: &[Vec3]) -> Self {
+        let strm = self.vec3_stream(b"GRAV", "Gravity Vector", "", UNIT_SCAL, samples);
+        self.stream(strm)
+    }
+
+    pub fn cori(self, samples: &[Quat]) -> Self {
+        let strm = self.quat_stream(b"CORI", "CameraOrientation", samples);
+        self.stream(strm)
+    }
+
+    pub fn iori(self, samples: &[Quat]) -> Self {
+        let strm = self.quat_stream(b"IORI", "ImageOrientation", samples);
+        self.stream(strm)
+    }
+
+    /// Adds a `SHUT` stream of exposure times in _s_.
+    pub fn shut(self, exposures: &[f32]) -> Self {
+        let strm = self.stream_header("Exposure time (shutter speed)", exposures.len())
+            .units("s")
+            .klv(f32s(b"SHUT", 1, exposures));
+        self.stream(strm)
+    }
+
+    pub fn isoe(self, isos: &[u16]) -> Self {
+        let strm = self.stream_header("Sensor ISO", isos.len()).klv(u16s(b"ISOE", isos));
+        self.stream(strm)
+    }
+
+    pub fn isog(self, gains: &[f32]) -> Self {
+        let strm = self.stream_header("Sensor gain (ISO x100)", gains.len()).klv(f32s(b"ISOG", 1, gains));
+        self.stream(strm)
+    }
+
+    /// Adds a `WBAL` stream of white balances in _K_.
+    pub fn wbal(self, kelvins: &[u16]) -> Self {
+        let strm = self.stream_header("White Balance temperature (Kelvin)", kelvins.len()).klv(u16s(b"WBAL", kelvins));
+        self.stream(strm)
+    }
+
+    pub fn wrgb(self, gains: &[Rgb]) -> Self {
+        let values: Vec<f32> = gains.iter().flat_map(|rgb| [rgb.r, rgb.g, rgb.b]).collect();
+        let strm = self.stream_header("White Balance RGB gains", gains.len()).klv(f32s(b"WRGB", 3, &values));
+        self.stream(strm)
+    }
+
+    /// Adds a `YAVG` stream of average lumas in `[0, 255]`.
+    pub fn yavg(self, lumas: &[u8]) -> Self {
+        let strm = self.stream_header("Average luminance", lumas.len())
+            .klv(klv(b"YAVG", b'B', 1, lumas.len() as u16, lumas));
+        self.stream(strm)
+    }
+
+    /// Adds a `UNIF` stream of image uniformities in `[0, 1]`.
+    pub fn unif(self, uniformities: &[f32]) -> Self {
+        let strm = self.stream_header("Image uniformity", uniformities.len()).klv(f32s(b"UNIF", 1, uniformities));
+        self.stream(strm)
+    }
+
+    /// Adds an `MSKP` stream of main video frame skips.
+    pub fn mskp(self, skips: &[u16]) -> Self {
+        let strm = self.stream_header("Main video frame SKiP", skips.len()).klv(u16s(b"MSKP", skips));
+        self.stream(strm)
+    }
+
+    /// Adds an `LSKP` stream of low-res video frame skips.
+    pub fn lskp(self, skips: &[u16]) -> Self {
+        let strm = self.stream_header("Low res video frame SKiP", skips.len()).klv(u16s(b"LSKP", skips));
+        self.stream(strm)
+    }
+
+    pub fn wndm(self, samples: &[Wind]) -> Self {
+        let data: Vec<u8> = samples.iter().flat_map(|w| [w.enabled as u8, w.level as u8]).collect();
+        let strm = self.stream_header("Wind Processing[wind_enable, meter_value(0 - 100)]", samples.len())
+            .klv(klv(b"WNDM", b'B', 2, samples.len() as u16, &data));
+        self.stream(strm)
+    }
+
+    pub fn mwet(self, samples: &[MicWet]) -> Self {
+        let data: Vec<u8> = samples.iter().flat_map(|m| [m.wet as u8, m.all_mics as u8, m.confidence as u8]).collect();
+        let strm = self.stream_header("Microphone Wet[mic_wet, all_mics, confidence]", samples.len())
+            .klv(klv(b"MWET", b'B', 3, samples.len() as u16, &data));
+        self.stream(strm)
+    }
+
+    /// Adds an `AALP` stream of levels in _dBFS_.
+    pub fn aalp(self, samples: &[AudioLevel]) -> Self {
+        let data: Vec<u8> = samples.iter().flat_map(|a| [a.rms as i8 as u8, a.peak as i8 as u8]).collect();
+        let strm = self.stream_header("AGC audio level[rms_level ,peak_level]", samples.len())
+            .units("dBFS")
+            .klv(klv(b"AALP", b'b', 2, samples.len() as u16, &data));
+        self.stream(strm)
+    }
+
+    /// Adds a `FACE` stream with one KLV per frame, laid out as `layout`.
+    /// Confidence and smile default to 0 where the layout has them.
+    pub fn faces(self, layout: FaceLayout, frames: &[Vec<Face>]) -> Self {
+        let mut strm = self
+            .stream_header("Face Coordinates and details", frames.len())
+            .klv(ascii(b"TYPE", layout.type_str()));
+        // HERO8 and later store the box as u16 fractions of the frame.
+        let box_scal = u16::MAX as i32;
+        strm = match layout {
+            FaceLayout::Hero6 | FaceLayout::Hero7 => strm,
+            FaceLayout::Hero8 => strm.klv(s32s(b"SCAL", &[1, 1, 1, box_scal, box_scal, box_scal, box_scal, 1, 1])),
+            FaceLayout::Hero10 => strm.klv(s32s(b"SCAL", &[1, 1, 1, box_scal, box_scal, box_scal, box_scal])),
+        };
+
+        let struct_size: usize = ValueType::parse_struct_type(layout.type_str())
+            .unwrap()
+            .iter()
+            .map(ValueType::element_size)
+            .sum();
+        for faces in frames {
+            let mut data = Vec::new();
+            for face in faces {
+                let bbox = [face.x, face.y, face.w, face.h];
+                let confidence = face.confidence.unwrap_or(0.0) as u8;
+                let smile = face.smile.unwrap_or(0.0);
+                match layout {
+                    FaceLayout::Hero6 | FaceLayout::Hero7 => {
+                        data.extend(face.id.to_be_bytes());
+                        data.extend(bbox.iter().flat_map(|v| v.to_be_bytes()));
+                        if layout == FaceLayout::Hero7 {
+                            data.extend([0; 17 * 4]);
+                            data.extend(smile.to_be_bytes());
+                        }
+                    }
+                    FaceLayout::Hero8 | FaceLayout::Hero10 => {
+                        data.extend([1, confidence]);
+                        data.extend((face.id as u16).to_be_bytes());
+                        data.extend(bbox.iter().flat_map(|&v| (scale_i32(v, box_scal) as u16).to_be_bytes()));
+                        if layout == FaceLayout::Hero8 {
+                            data.extend([smile as u8, 0]);
+                        }
+                    }
+                }
+            }
+            strm = strm.klv(klv(b"FACE", b'?', struct_size as u8, faces.len() as u16, &data));
+        }
+        self.stream(strm)
+    }
+
+    /// Adds a `SCEN` stream with one KLV per frame.
+    pub fn scen(self, frames: &[Vec<SceneProbability>]) -> Self {
+        let mut strm = self.stream_header("Scene Classification[[CLASSIFIER_FOUR_CC,prob], ...]", frames.len())
+            .klv(ascii(b"TYPE", "Ff"));
+        for scenes in frames {
+            let data: Vec<u8> = scenes
+                .iter()
+                .flat_map(|scene| {
+                    let class = match scene.class {
+                        SceneClass::Snow => *b"SNOW",
+                        SceneClass::Urban => *b"URBA",
+                        SceneClass::Indoor => *b"INDO",
+                        SceneClass::Water => *b"WATR",
+                        SceneClass::Vegetation => *b"VEGE",
+                        SceneClass::Beach => *b"BEAC",
+                        SceneClass::Other(fourcc) => fourcc.0,
+                    };
+                    [class, scene.probability.to_be_bytes()].concat()
+                })
+                .collect();
+            strm = strm.klv(klv(b"SCEN", b'?', 8, scenes.len() as u16, &data));
+        }
+        self.stream(strm)
+    }
+
+    /// Adds a `HUES` stream with one KLV per frame.
+    pub fn hues(self, frames: &[Vec<Hue>]) -> Self {
+        let mut strm = self.stream_header("Predominant hue[[hue, weight], ...]", frames.len())
+            .klv(ascii(b"TYPE", "BB"));
+        for hues in frames {
+            let data: Vec<u8> = hues
+                .iter()
+                .flat_map(|hue| [(hue.hue * 255.0 / 360.0 + 0.5) as u8, (hue.weight * 255.0 + 0.5) as u8])
+                .collect();
+            strm = strm.klv(klv(b"HUES", b'?', 2, hues.len() as u16, &data));
+        }
+        self.stream(strm)
+    }
+
+    /// Adds an arbitrary `STRM`.
+    pub fn stream(mut self, strm: StreamBuilder) -> Self {
+        self.streams.push(strm.build());
+        self
+    }
+
+    /// Encodes the `DEVC` KLV.
+    pub fn build(&self) -> Vec<u8> {
+        let mut children = alloc::vec![
+            u32s(b"DVID", &[self.device_id]),
+            ascii(b"DVNM", &self.device_name),
+        ];
+        children.extend(self.streams.iter().cloned());
+        nested(b"DEVC", &children)
+    }
+
+    /// `STNM` and `TSMP` for a stream with `count` samples per payload.
+    fn stream_header(&self, name: &str, count: usize) -> StreamBuilder {
+        StreamBuilder::new()
+            .name(name)
+            .tsmp((self.sample_index + 1) * count as u32)
+    }
+
+    fn vec3_stream(&self, fourcc: &[u8; 4], name: &str, units: &str, scal: i16, samples: &[Vec3]) -> StreamBuilder {
+        let values: Vec<i16> = samples
+            .iter()
+            .flat_map(|v| [v.x, v.y, v.z])
+            .map(|value| scale_i16(value, scal))
+            .collect();
+        let strm = self.stream_header(name, samples.len());
+        let strm = if units.is_empty() { strm } else { strm.units(units) };
+        let strm = strm.klv(s16s(b"SCAL", &[scal]));
+        let strm = match self.temperature {
+            Some(celsius) if matches!(fourcc, b"ACCL" | b"GYRO") => strm.klv(f32s(b"TMPC", 1, &[celsius])),
+            _ => strm,
+        };
+        strm.klv(s16_structs(fourcc, 3, &values))
+    }
+
+    fn quat_stream(&self, fourcc: &[u8; 4], name: &str, samples: &[Quat]) -> StreamBuilder {
+        let values: Vec<i16> = samples
+            .iter()
+            .flat_map(|q| [q.w, q.x, q.y, q.z])
+            .map(|value| scale_i16(value, UNIT_SCAL))
+            .collect();
+        self.stream_header(name, samples.len())
+            .klv(s16s(b"SCAL", &[UNIT_SCAL]))
+            .klv(s16_structs(fourcc, 4, &values))
+    }
+}
+
+/// Like [`s16s`], with one sample per `axis_count` values, as cameras write
+/// multi-axis streams.
+fn s16_structs(fourcc: &[u8; 4], axis_count: usize, values: &[i16]) -> Vec<u8> {
+    let data: Vec<u8> = values.iter().flat_map(|v| v.to_be_bytes()).collect();
+    klv(fourcc, b's', (2 * axis_count) as u8, (values.len() / axis_count) as u16, &data)
+}
+
+fn scale_i32(value: f32, scal: i32) -> i32 {
+    let scaled = value as f64 * scal as f64;
+    (if scaled < 0.0 { scaled - 0.5 } else { scaled + 0.5 }) as i32
+}
+
+fn scale_i16(value: f32, scal: i16) -> i16 {
+    let scaled = value * scal as f32;
+    (if scaled < 0.0 { scaled - 0.5 } else { scaled + 0.5 }) as i16
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::klv::{Fourcc, Klv, Value};
+
+    #[test]
+    fn typical_devc_carries_sticky_metadata() {
+        let bytes = DevcBuilder::typical(2).build();
+        let klvs = Klv::from_bytes(&bytes).unwrap();
+        assert_eq!(klvs.len(), 1);
+
+        let Value::Nested(children) = klvs[0].value() else { panic!("DEVC should be nested") };
+        assert!(matches!(children[1].value(), Value::Ascii(s) if s == "Camera"));
+
+        let Value::Nested(accl_strm) = children[2].value() else { panic!("STRM should be nested") };
+        let fourccs: Vec<Fourcc> = accl_strm.iter().map(|k| k.header().fourcc()).collect();
+        assert_eq!(fourccs, [*b"STNM", *b"TSMP", *b"SIUN", *b"SCAL", *b"ACCL"].map(Fourcc));
+        assert!(matches!(accl_strm[1].value(), Value::U32(v) if v[..] == [600]));
+        assert!(matches!(accl_strm[2].value(), Value::Ascii(s) if s == "m/s²"));
+    }
+
+    #[test]
+    fn gps5_stream_round_trips() {
+        let point = Gps5 { latitude: 35.5, longitude: 139.25, altitude: 12.0, speed_2d: 1.0, speed_3d: 1.0 };
+        let bytes = DevcBuilder::new(1, "Camera").gps5(&[point; 18], 3, 1.5).build();
+        let klvs = Klv::from_bytes(&bytes).unwrap();
+
+        let Value::Nested(children) = klvs[0].value() else { panic!("DEVC should be nested") };
+        let Value::Nested(strm) = children[2].value() else { panic!("STRM should be nested") };
+        let gps5 = strm.last().unwrap();
+        assert_eq!(gps5.header().fourcc().as_str(), "GPS5");
+        assert!(matches!(gps5.value(), Value::S32(v) if v.len() == 18 * 5 && v[0] == 355_000_000 && v[1] == 1_392_500_000));
+    }
+}
@@ -0,0 +1,145 @@
+//! `TMPC` sensor temperature and temperature-dependent gyro bias.
+//!
+//! `TMPC` is sticky metadata of the IMU `STRM`s (`ACCL`, `GYRO`) rather than a
+//! stream of its own, holding one reading per payload.
+
+use alloc::vec::Vec;
+
+use crate::decode::{DecodedStream, StreamContext, StreamKlvs};
+use crate::klv::{Fourcc, Value};
+use crate::{GpmfSample, Vec3};
+
+/// A source of the gyro bias at a given sensor temperature.
+///
+/// Implement this to plug a calibration of a specific camera into
+/// [`GpmfSample::gyro_compensated`].
+pub trait GyroBiasModel {
+    /// Bias, in _rad/s_, to subtract from `GYRO` samples taken at
+    /// `temperature` _°C_.
+    fn bias(&self, temperature: f32) -> Vec3;
+}
+
+/// A bias that varies linearly with temperature around `reference`.
+#[derive(Debug, Clone, Copy)]
+pub struct LinearGyroBias {
+    /// Temperature, in _°C_, at which the bias is `offset`.
+    pub reference: f32,
+    /// Bias at `reference`, in _rad/s_.
+    pub offset: Vec3,
+    /// Change of the bias per _°C_, in _rad/s_.
+    pub per_degree: Vec3,
+}
+
+impl GyroBiasModel for LinearGyroBias {
+    fn bias(&self, temperature: f32) -> Vec3 {
+        let delta = temperature - self.reference;
+        Vec3 {
+            x: self.offset.x + self.per_degree.x * delta,
+            y: self.offset.y + self.per_degree.y * delta,
+            z: self.offset.z + self.per_degree.z * delta,
+        }
+    }
+}
+
+impl GpmfSample {
+    /// IMU temperature in _°C_, taken from the first `STRM` that carries a
+    /// `TMPC`, which is `ACCL` on GoPro cameras. `None` when there is none.
+    pub fn tmpc(&self) -> Option<f32> {
+        self.decoded_as::<f32>(Fourcc(*b"TMPC")).copied()
+    }
+
+    /// Temperature in _°C_ from the `TMPC` of the `STRM` whose data KLV is
+    /// `fourcc`, e.g. `"GYRO"`. `None` when that stream has no `TMPC`.
+    pub fn stream_tmpc(&self, fourcc: &str) -> Option<f32> {
+        let stream = self
+            .klvs()
+            .iter()
+            .filter_map(|klv| match klv.value() {
+                Value::Nested(children) if klv.header().fourcc().as_str() == "STRM" => StreamKlvs::new(children),
+                _ => None,
+            })
+            .find(|stream| stream.data().header().fourcc().as_str() == fourcc)?;
+        crate::numbers_as_f32(stream.sticky("TMPC")?.value())?.first().copied()
+    }
+
+    /// `GYRO` samples with the bias `model` predicts at the `TMPC` of the
+    /// `GYRO` stream subtracted. Samples are returned as-is when that stream
+    /// has no `TMPC`.
+    pub fn gyro_compensated(&self, model: &dyn GyroBiasModel) -> Vec<Vec3> {
+        let Some(temperature) = self.stream_tmpc("GYRO") else {
+            return self.gyro().to_vec();
+        };
+        let bias = model.bias(temperature);
+        self.gyro()
+            .iter()
+            .map(|v| Vec3 { x: v.x - bias.x, y: v.y - bias.y, z: v.z - bias.z })
+            .collect()
+    }
+}
+
+/// Decodes a `TMPC` KLV into an `f32`. Unlike the streams it is embedded in,
+/// `TMPC` is not subject to `SCAL`.
+pub(crate) fn decode_tmpc(stream: &StreamContext<'_>) -> Option<DecodedStream> {
+    let temperature = *crate::numbers_as_f32(stream.data().value())?.first()?;
+    Some(DecodedStream::new(temperature))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::synth::DevcBuilder;
+    use crate::Klv;
+
+    #[test]
+    fn tmpc_is_read_from_imu_stream_and_compensates_gyro() {
+        let gyro = [Vec3 { x: 0.1, y: -0.2, z: 0.05 }; 10];
+        let bytes = DevcBuilder::new(1, "Camera").tmpc(48.5).gyro(&gyro).build();
+        let klvs = Klv::from_bytes(&bytes).unwrap();
+        let sample = GpmfSample::new(&klvs[0]);
+
+        assert_eq!(sample.tmpc(), Some(48.5));
+
+        let model = LinearGyroBias {
+            reference: 40.0,
+            offset: Vec3 { x: 0.05, y: 0.0, z: 0.0 },
+            per_degree: Vec3 { x: 0.0, y: -0.02, z: 0.0 },
+        };
+        let compensated = sample.gyro_compensated(&model);
+        assert_eq!(compensated.len(), 10);
+        assert!((compensated[0].x - 0.05).abs() < 1e-3);
+        assert!((compensated[0].y - (-0.2 + 0.17)).abs() < 1e-3);
+        assert!((compensated[0].z - 0.05).abs() < 1e-3);
+    }
+
+    #[test]
+    fn gyro_is_compensated_at_its_own_temperature() {
+        let bytes = DevcBuilder::new(1, "Camera")
+            .tmpc(30.0)
+            .accl(&[Vec3 { x: 0.0, y: 9.8, z: 0.0 }])
+            .tmpc(50.0)
+            .gyro(&[Vec3 { x: 0.0, y: 0.0, z: 0.0 }])
+            .build();
+        let klvs = Klv::from_bytes(&bytes).unwrap();
+        let sample = GpmfSample::new(&klvs[0]);
+
+        assert_eq!(sample.tmpc(), Some(30.0));
+        assert_eq!(sample.stream_tmpc("ACCL"), Some(30.0));
+        assert_eq!(sample.stream_tmpc("GYRO"), Some(50.0));
+        assert_eq!(sample.stream_tmpc("GPS9"), None);
+
+        let model = LinearGyroBias { reference: 40.0, offset: Vec3 { x: 0.0, y: 0.0, z: 0.0 }, per_degree: Vec3 { x: 0.01, y: 0.0, z: 0.0 } };
+        assert!((sample.gyro_compensated(&model)[0].x - (-0.1)).abs() < 1e-3);
+    }
+
+    #[test]
+    fn gyro_is_unchanged_without_tmpc() {
+        let bytes = DevcBuilder::new(1, "Camera").gyro(&[Vec3 { x: 0.1, y: 0.0, z: 0.0 }]).build();
+        let klvs = Klv::from_bytes(&bytes).unwrap();
+        let sample = GpmfSample::new(&klvs[0]);
+
+        assert_eq!(sample.tmpc(), None);
+        let model = LinearGyroBias { reference: 0.0, offset: Vec3 { x: 1.0, y: 1.0, z: 1.0 }, per_degree: Vec3 { x: 0.0, y: 0.0, z: 0.0 } };
+        assert!((sample.gyro_compensated(&model)[0].x - 0.1).abs() < 1e-3);
+    }
+}
@@ -0,0 +1,466 @@
+//! Conformance checks of GPMF payloads against the GPMF specification.
+//!
+//! Unlike [`Klv::from_bytes`](crate::Klv::from_bytes), the checks walk the
+//! raw bytes, so they report every problem found, with its offset, instead of
+//! stopping at the first one:
+//!
+//! - Payloads, KLVs and their padding are 4-byte aligned, padding is zero.
+//! - Nested KLVs are filled exactly by their children.
+//! - Type characters are known, and sample sizes are a multiple of the type's
+//!   size; for `?` values, of the size of the struct described by `TYPE`.
+//! - `SCAL` has either one value or one per element of the data it scales.
+//! - Well-known FourCCs have the type the specification gives them.
+//! - `TSMP` never decreases across payloads and advances by the number of
+//!   samples in each payload.
+
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::decode::split_stream;
+use crate::klv::{Fourcc, ValueType};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    /// Unusual, but readable.
+    Warning,
+    /// Violates the specification; readers may fail or misread the data.
+    Error,
+}
+
+/// A problem found by [`Validator`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Issue {
+    pub severity: Severity,
+    /// Offset of the KLV header within the payload.
+    pub offset: usize,
+    /// FourCCs from the top level down to the offending KLV, e.g.
+    /// `DEVC/STRM/SCAL`. Empty for issues with the payload as a whole.
+    pub path: String,
+    pub message: String,
+}
+
+impl core::fmt::Display for Issue {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let severity = match self.severity {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+        if self.path.is_empty() {
+            write!(f, "{} at 0x{:08x}: {}", severity, self.offset, self.message)
+        } else {
+            write!(f, "{} at 0x{:08x} ({}): {}", severity, self.offset, self.path, self.message)
+        }
+    }
+}
+
+/// Types the specification gives well-known FourCCs, as type characters.
+const EXPECTED_TYPES: &[(&[u8; 4], &str)] = &[
+    (b"DEVC", "\0"),
+    (b"STRM", "\0"),
+    (b"DVID", "LF"),
+    (b"DVNM", "c"),
+    (b"STNM", "c"),
+    (b"RMRK", "c"),
+    (b"SIUN", "c"),
+    (b"UNIT", "c"),
+    (b"TYPE", "c"),
+    (b"SCAL", "bBsSlLf"),
+    (b"TSMP", "L"),
+    (b"TIMO", "f"),
+    (b"EMPT", "L"),
+    (b"TICK", "L"),
+    (b"TOCK", "L"),
+    (b"STMP", "J"),
+    (b"TMPC", "f"),
+    (b"ORIN", "c"),
+    (b"ORIO", "c"),
+    (b"MTRX", "f"),
+    (b"ACCL", "s"),
+    (b"GYRO", "s"),
+    (b"MAGN", "s"),
+    (b"GRAV", "s"),
+    (b"CORI", "s"),
+    (b"IORI", "s"),
+    (b"GPS5", "l"),
+    (b"GPS9", "?"),
+    (b"GPSF", "L"),
+    (b"GPSP", "S"),
+    (b"GPSU", "U"),
+    (b"FACE", "?"),
+    (b"SCEN", "?"),
+    (b"HUES", "?"),
+];
+
+/// A KLV header as found in the bytes, which may not be parseable.
+#[derive(Debug, Clone, Copy)]
+struct RawHeader {
+    fourcc: Fourcc,
+    type_char: u8,
+    sample_size: usize,
+    repeat: usize,
+}
+
+impl RawHeader {
+    fn parse(bytes: &[u8]) -> Self {
+        Self {
+            fourcc: Fourcc(bytes[0..4].try_into().unwrap()),
+            type_char: bytes[4],
+            sample_size: bytes[5] as usize,
+            repeat: u16::from_be_bytes([bytes[6], bytes[7]]) as usize,
+        }
+    }
+
+    fn data_size(&self) -> usize {
+        self.sample_size * self.repeat
+    }
+}
+
+/// A non-nested KLV met while walking a container, kept to check the data
+/// KLV of a `STRM` against its sticky metadata.
+#[derive(Debug, Clone, Copy)]
+struct RawKlv<'a> {
+    header: RawHeader,
+    offset: usize,
+    data: &'a [u8],
+}
+
+/// Checks payloads one after the other, keeping what is needed to compare
+/// consecutive payloads.
+#[derive(Debug, Default)]
+pub struct Validator {
+    /// Last `TSMP` of each stream, by `DVID` and FourCC of the data KLV.
+    tsmp: BTreeMap<(Option<u32>, Fourcc), u32>,
+}
+
+impl Validator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Checks the next payload of a track, i.e. the bytes of one MP4 sample.
+    pub fn validate_payload(&mut self, bytes: &[u8]) -> Vec<Issue> {
+        let mut walker = Walker {
+            validator: self,
+            issues: Vec::new(),
+            path: Vec::new(),
+            device_id: None,
+        };
+        if !bytes.len().is_multiple_of(4) {
+            walker.issue(Severity::Error, 0, format!("Payload size {} is not a multiple of 4", bytes.len()));
+        }
+        walker.walk_container(bytes, 0);
+        walker.issues
+    }
+}
+
+/// Checks a single payload. See [`Validator`] to check a sequence of them.
+pub fn validate(bytes: &[u8]) -> Vec<Issue> {
+    Validator::new().validate_payload(bytes)
+}
+
+/// `fourcc` with bytes other than printable ASCII escaped.
+fn fourcc_string(fourcc: &Fourcc) -> String {
+    fourcc.0.iter().flat_map(|&b| char::from(b).escape_default()).collect()
+}
+
+struct Walker<'v> {
+    validator: &'v mut Validator,
+    issues: Vec<Issue>,
+    path: Vec<Fourcc>,
+    device_id: Option<u32>,
+}
+
+impl Walker<'_> {
+    fn issue(&mut self, severity: Severity, offset: usize, message: String) {
+        let path = self.path.iter().map(fourcc_string).collect::<Vec<_>>().join("/");
+        self.issues.push(Issue { severity, offset, path, message });
+    }
+
+    /// Reports an issue about `klv`, a child of the `STRM` being checked.
+    fn stream_issue(&mut self, severity: Severity, klv: &RawKlv<'_>, message: String) {
+        self.path.push(klv.header.fourcc);
+        self.issue(severity, klv.offset, message);
+        self.path.pop();
+    }
+
+    /// Walks the children of a container occupying `bytes`, which start at
+    /// `base` within the payload. Returns the non-nested children.
+    fn walk_container<'a>(&mut self, bytes: &'a [u8], base: usize) -> Vec<RawKlv<'a>> {
+        let mut values = Vec::new();
+        let mut pos = 0;
+        while pos < bytes.len() {
+            let offset = base + pos;
+            let rest = &bytes[pos..];
+            if rest.len() < 8 || rest[0..4] == [0; 4] {
+                if rest.iter().any(|&b| b != 0) {
+                    self.issue(Severity::Error, offset, format!("{} trailing bytes are not a KLV", rest.len()));
+                } else if !self.path.is_empty() {
+                    self.issue(Severity::Warning, offset, format!("{} bytes of zero padding after the last KLV", rest.len()));
+                }
+                break;
+            }
+
+            let header = RawHeader::parse(rest);
+            self.path.push(header.fourcc);
+            let Some(size) = self.check_header(&header, offset, rest.len() - 8) else {
+                self.path.pop();
+                break;
+            };
+            let data = &rest[8..8 + header.data_size()];
+            if rest[8 + header.data_size()..8 + size].iter().any(|&b| b != 0) {
+                self.issue(Severity::Warning, offset, "Padding bytes are not zero".into());
+            }
+
+            if header.type_char == 0 {
+                if !header.data_size().is_multiple_of(4) {
+                    self.issue(Severity::Error, offset, format!("Nested size {} is not a multiple of 4", header.data_size()));
+                }
+                let device_id = self.device_id;
+                let children = self.walk_container(data, offset + 8);
+                match header.fourcc.as_bytes() {
+                    b"DEVC" => self.device_id = device_id,
+                    b"STRM" => self.check_stream(&children),
+                    _ => {}
+                }
+            } else {
+                let klv = RawKlv { header, offset, data };
+                if header.fourcc.as_bytes() == b"DVID" && header.type_char == b'L' && data.len() >= 4 {
+                    self.device_id = Some(u32::from_be_bytes(data[0..4].try_into().unwrap()));
+                }
+                values.push(klv);
+            }
+            self.path.pop();
+            pos += 8 + size;
+        }
+        values
+    }
+
+    /// Checks a header against the `available` bytes following it, and
+    /// returns the size of its data with padding, or `None` if it doesn't fit.
+    fn check_header(&mut self, header: &RawHeader, offset: usize, available: usize) -> Option<usize> {
+        if !header.fourcc.0.iter().all(|&b| b.is_ascii_alphanumeric() || b == b' ') {
+            self.issue(Severity::Warning, offset, format!("FourCC \"{}\" has non-alphanumeric characters", fourcc_string(&header.fourcc)));
+        }
+
+        match ValueType::try_from(header.type_char) {
+            Err(_) => self.issue(
+                Severity::Error,
+                offset,
+                format!("Unknown type '{}' (0x{:02x})", char::from(header.type_char).escape_default(), header.type_char),
+            ),
+            Ok(value_type) => {
+                let element_size = value_type.element_size();
+                if element_size > 0 && !header.sample_size.is_multiple_of(element_size) {
+                    self.issue(
+                        Severity::Error,
+                        offset,
+                        format!("Sample size {} is not a multiple of {}, the size of type '{}'", header.sample_size, element_size, value_type.as_char()),
+                    );
+                }
+            }
+        }
+
+        let expected = EXPECTED_TYPES.iter().find(|(fourcc, _)| *fourcc == header.fourcc.as_bytes());
+        if let Some((_, expected)) = expected.filter(|(_, expected)| !expected.as_bytes().contains(&header.type_char)) {
+            self.issue(
+                Severity::Warning,
+                offset,
+                format!(
+                    "Type '{}' where the specification gives '{}'",
+                    char::from(header.type_char).escape_default(),
+                    expected.escape_default(),
+                ),
+            );
+        }
+
+        let size = header.data_size().next_multiple_of(4);
+        if header.data_size() > available {
+            self.issue(
+                Severity::Error,
+                offset,
+                format!("Data size {} exceeds the {} bytes left in the container", header.data_size(), available),
+            );
+            return None;
+        }
+        if size > available {
+            self.issue(Severity::Error, offset, format!("Padding to {} bytes exceeds the container", size));
+            return None;
+        }
+        Some(size)
+    }
+
+    /// Checks the data KLV of a `STRM`, i.e. its last non-nested child,
+    /// against the sticky KLVs preceding it.
+    fn check_stream(&mut self, children: &[RawKlv<'_>]) {
+        let Some((data, sticky)) = split_stream(children) else {
+            return;
+        };
+        let find = |fourcc: &[u8; 4]| sticky.iter().rev().find(|klv| klv.header.fourcc.as_bytes() == fourcc);
+
+        let mut elements = ValueType::try_from(data.header.type_char)
+            .ok()
+            .map(|value_type| match value_type.element_size() {
+                0 => 1,
+                element_size => data.header.sample_size / element_size,
+            });
+        if data.header.type_char == b'?' {
+            elements = None;
+            match find(b"TYPE") {
+                None => self.stream_issue(Severity::Error, data, "Complex data without a TYPE".into()),
+                Some(type_klv) => {
+                    let type_str: String = type_klv.data.iter().take_while(|&&b| b != 0).map(|&b| char::from(b)).collect();
+                    match ValueType::parse_struct_type(&type_str) {
+                        Err(e) => self.stream_issue(Severity::Error, type_klv, format!("{}", e)),
+                        Ok(types) => {
+                            let struct_size: usize = types.iter().map(ValueType::element_size).sum();
+                            if struct_size != data.header.sample_size {
+                                self.stream_issue(
+                                    Severity::Error,
+                                    data,
+                                    format!("Sample size {} does not match the {} bytes of TYPE \"{}\"", data.header.sample_size, struct_size, type_str),
+                                );
+                            }
+                            elements = Some(types.len());
+                        }
+                    }
+                }
+            }
+        }
+
+        if let (Some(scal), Some(elements)) = (find(b"SCAL"), elements) {
+            let scal_count = match ValueType::try_from(scal.header.type_char).map(|value_type| value_type.element_size()) {
+                Ok(element_size) if element_size > 0 => scal.header.data_size() / element_size,
+                _ => 0,
+            };
+            if scal_count != 1 && scal_count != elements {
+                self.stream_issue(
+                    Severity::Error,
+                    scal,
+                    format!("SCAL has {} values for data with {} elements per sample", scal_count, elements),
+                );
+            }
+        }
+
+        let tsmp_klv = find(b"TSMP").filter(|klv| klv.header.type_char == b'L' && klv.data.len() >= 4);
+        if let Some(tsmp_klv) = tsmp_klv {
+            let tsmp = u32::from_be_bytes(tsmp_klv.data[0..4].try_into().unwrap());
+            let samples: u32 = children
+                .iter()
+                .filter(|klv| klv.header.fourcc == data.header.fourcc)
+                .map(|klv| klv.header.repeat as u32)
+                .sum();
+            let key = (self.device_id, data.header.fourcc);
+            if let Some(&previous) = self.validator.tsmp.get(&key) {
+                if tsmp < previous {
+                    self.stream_issue(Severity::Error, tsmp_klv, format!("TSMP went back from {} to {}", previous, tsmp));
+                } else if tsmp - previous != samples {
+                    self.stream_issue(
+                        Severity::Warning,
+                        tsmp_klv,
+                        format!("TSMP advanced by {} but the payload has {} samples", tsmp - previous, samples),
+                    );
+                }
+            }
+            self.validator.tsmp.insert(key, tsmp);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use alloc::string::ToString;
+    use alloc::vec;
+
+    use crate::synth::{self, DevcBuilder, StreamBuilder};
+
+    fn messages(issues: &[Issue]) -> Vec<(Severity, &str, &str)> {
+        issues.iter().map(|issue| (issue.severity, issue.path.as_str(), issue.message.as_str())).collect()
+    }
+
+    #[test]
+    fn synthetic_payloads_are_valid() {
+        let mut validator = Validator::new();
+        for i in 0..3 {
+            let issues = validator.validate_payload(&DevcBuilder::typical(i).build());
+            assert!(issues.is_empty(), "{issues:?}");
+        }
+    }
+
+    #[test]
+    fn structure_errors_are_reported_with_offsets() {
+        let mut bytes = DevcBuilder::new(1, "Camera").stream(StreamBuilder::new().klv(synth::u32s(b"TSMP", &[1]))).build();
+        // Grow the STRM by 4 bytes, past the end of its DEVC.
+        let strm = bytes.windows(4).position(|w| w == b"STRM").unwrap();
+        bytes[strm + 7] += 4;
+
+        let issues = validate(&bytes);
+
+        assert_eq!(issues.len(), 1, "{issues:?}");
+        assert_eq!(issues[0].offset, strm);
+        assert_eq!(issues[0].severity, Severity::Error);
+        assert_eq!(issues[0].path, "DEVC/STRM");
+        assert!(issues[0].to_string().contains("exceeds"), "{}", issues[0]);
+
+        let issues = validate(&bytes[..bytes.len() - 2]);
+        assert!(issues.iter().any(|issue| issue.path.is_empty() && issue.message.contains("multiple of 4")));
+    }
+
+    #[test]
+    fn stream_metadata_is_checked() {
+        let gps9 = StreamBuilder::new()
+            .klv(synth::s32s(b"SCAL", &[1, 2]))
+            .klv(synth::ascii(b"TYPE", "lL"))
+            .klv(synth::klv(b"GPS9", b'?', 12, 1, &[0; 12]));
+        let accl = StreamBuilder::new().klv(synth::klv(b"ACCL", b'l', 6, 1, &[0; 6]));
+        let bytes = DevcBuilder::new(1, "Camera").stream(gps9).stream(accl).build();
+
+        let issues = validate(&bytes);
+
+        assert_eq!(messages(&issues), vec![
+            (Severity::Error, "DEVC/STRM/GPS9", "Sample size 12 does not match the 8 bytes of TYPE \"lL\""),
+            (Severity::Error, "DEVC/STRM/ACCL", "Sample size 6 is not a multiple of 4, the size of type 'l'"),
+            (Severity::Warning, "DEVC/STRM/ACCL", "Type 'l' where the specification gives 's'"),
+        ]);
+
+        let bytes = DevcBuilder::new(1, "Camera")
+            .stream(StreamBuilder::new().klv(synth::s16s(b"SCAL", &[1, 2])).klv(synth::s16s(b"ACCL", &[0; 6])))
+            .build();
+        assert_eq!(messages(&validate(&bytes)), vec![(
+            Severity::Error,
+            "DEVC/STRM/SCAL",
+            "SCAL has 2 values for data with 1 elements per sample"
+        )]);
+    }
+
+    #[test]
+    fn oversized_struct_types_are_errors() {
+        let strm = StreamBuilder::new()
+            .klv(synth::ascii(b"TYPE", "L[18446744073709551615]"))
+            .klv(synth::klv(b"GPS9", b'?', 4, 1, &[0; 4]));
+        let bytes = DevcBuilder::new(1, "Camera").stream(strm).build();
+
+        let issues = validate(&bytes);
+
+        assert_eq!(messages(&issues), vec![(
+            Severity::Error,
+            "DEVC/STRM/TYPE",
+            "TYPE \"L[18446744073709551615]\" describes a struct larger than the 255 bytes a sample can hold"
+        )]);
+    }
+
+    #[test]
+    fn tsmp_must_advance_by_sample_count() {
+        let mut validator = Validator::new();
+        assert!(validator.validate_payload(&DevcBuilder::typical(1).build()).is_empty());
+
+        let issues = validator.validate_payload(&DevcBuilder::typical(0).build());
+        assert!(issues.iter().any(|issue| issue.severity == Severity::Error && issue.message.starts_with("TSMP went back")));
+
+        let issues = validator.validate_payload(&DevcBuilder::typical(2).build());
+        assert!(issues.iter().any(|issue| issue.severity == Severity::Warning && issue.message.starts_with("TSMP advanced by")));
+    }
+}
@@ -0,0 +1,162 @@
+//! Depth-first traversal of a KLV tree.
+
+use alloc::vec::Vec;
+
+use crate::klv::{Fourcc, Klv, Value};
+
+/// The non-nested KLVs preceding some point of a traversal.
+///
+/// In GPMF, sticky metadata (`SCAL`, `TYPE`, `SIUN`, `STNM`, `TMPC`, ...)
+/// applies to the KLVs following it in the same container, including those in
+/// nested containers. The walk doesn't tell metadata from data, so the scope
+/// holds every earlier non-nested KLV of the enclosing containers, data KLVs
+/// included. Entries from inner containers shadow outer ones.
+#[derive(Debug, Clone, Default)]
+pub struct StickyScope<'a> {
+    /// Outermost first.
+    klvs: Vec<&'a Klv>,
+}
+
+impl<'a> StickyScope<'a> {
+    /// Returns the innermost KLV in scope with the given FourCC.
+    pub fn get(&self, fourcc: &str) -> Option<&'a Klv> {
+        self.klvs
+            .iter()
+            .rev()
+            .find(|klv| klv.header().fourcc().as_str() == fourcc)
+            .copied()
+    }
+
+    /// Iterates over the KLVs in scope, innermost first.
+    pub fn iter(&self) -> impl Iterator<Item = &'a Klv> + '_ {
+        self.klvs.iter().rev().copied()
+    }
+}
+
+/// Callbacks invoked by [`Klv::walk`].
+///
+/// `path` always ends with the FourCC of the KLV being visited, e.g.
+/// `[DEVC, STRM, SCAL]`. `scope` holds the non-nested KLVs that precede it.
+pub trait KlvVisitor<'a> {
+    /// Called for a `Nested` KLV before its children. Return `false` to skip
+    /// the children (and the matching [`KlvVisitor::leave_container`] call).
+    fn enter_container(&mut self, path: &[Fourcc], klv: &'a Klv, scope: &StickyScope<'a>) -> bool {
+        let _ = (path, klv, scope);
+        true
+    }
+
+    /// Called for every non-`Nested` KLV.
+    fn visit_value(&mut self, path: &[Fourcc], klv: &'a Klv, scope: &StickyScope<'a>) {
+        let _ = (path, klv, scope);
+    }
+
+    /// Called for a `Nested` KLV after its children.
+    fn leave_container(&mut self, path: &[Fourcc], klv: &'a Klv) {
+        let _ = (path, klv);
+    }
+}
+
+impl Klv {
+    /// Walks this KLV and its descendants depth-first.
+    pub fn walk<'a, V: KlvVisitor<'a> + ?Sized>(&'a self, visitor: &mut V) {
+        Self::walk_all(core::slice::from_ref(self), visitor);
+    }
+
+    /// Walks a sequence of sibling KLVs, e.g. the result of [`Klv::from_bytes`].
+    pub fn walk_all<'a, V: KlvVisitor<'a> + ?Sized>(klvs: &'a [Klv], visitor: &mut V) {
+        let mut path: Vec<Fourcc> = Vec::new();
+        let mut scope = StickyScope::default();
+        walk_siblings(klvs, visitor, &mut path, &mut scope);
+    }
+}
+
+fn walk_siblings<'a, V: KlvVisitor<'a> + ?Sized>(
+    klvs: &'a [Klv],
+    visitor: &mut V,
+    path: &mut Vec<Fourcc>,
+    scope: &mut StickyScope<'a>,
+) {
+    let scope_len = scope.klvs.len();
+
+    for klv in klvs {
+        path.push(klv.header().fourcc());
+        match klv.value() {
+            Value::Nested(children) => {
+                if visitor.enter_container(path, klv, scope) {
+                    walk_siblings(children, visitor, path, scope);
+                    visitor.leave_container(path, klv);
+                }
+            }
+            _ => {
+                visitor.visit_value(path, klv, scope);
+                scope.klvs.push(klv);
+            }
+        }
+        path.pop();
+    }
+
+    scope.klvs.truncate(scope_len);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use alloc::string::{String, ToString as _};
+
+    #[derive(Default)]
+    struct Recorder {
+        events: Vec<String>,
+    }
+
+    fn path_str(path: &[Fourcc]) -> String {
+        path.iter().map(|f| f.as_str()).collect::<Vec<_>>().join("/")
+    }
+
+    impl<'a> KlvVisitor<'a> for Recorder {
+        fn enter_container(&mut self, path: &[Fourcc], _klv: &'a Klv, _scope: &StickyScope<'a>) -> bool {
+            self.events.push(alloc::format!("enter {}", path_str(path)));
+            true
+        }
+
+        fn visit_value(&mut self, path: &[Fourcc], _klv: &'a Klv, scope: &StickyScope<'a>) {
+            let scal = match scope.get("SCAL").map(|k| k.value()) {
+                Some(Value::S16(v)) => v[0].to_string(),
+                _ => "-".to_string(),
+            };
+            self.events.push(alloc::format!("value {} scal={}", path_str(path), scal));
+        }
+
+        fn leave_container(&mut self, path: &[Fourcc], _klv: &'a Klv) {
+            self.events.push(alloc::format!("leave {}", path_str(path)));
+        }
+    }
+
+    #[test]
+    fn walk_reports_paths_and_sticky_scope() {
+        #[rustfmt::skip]
+        let bytes: &[u8] = &[
+            b'D', b'E', b'V', b'C', 0, 1, 0, 56,
+                b'S', b'C', b'A', b'L', b's', 2, 0, 1, 0, 10, 0, 0,
+                b'S', b'T', b'R', b'M', 0, 1, 0, 24,
+                    b'S', b'C', b'A', b'L', b's', 2, 0, 1, 0, 20, 0, 0,
+                    b'A', b'C', b'C', b'L', b's', 2, 0, 1, 0, 1, 0, 0,
+                b'G', b'Y', b'R', b'O', b's', 2, 0, 1, 0, 1, 0, 0,
+        ];
+        let klvs = Klv::from_bytes(bytes).unwrap();
+
+        let mut recorder = Recorder::default();
+        Klv::walk_all(&klvs, &mut recorder);
+
+        assert_eq!(recorder.events, [
+            "enter DEVC",
+            "value DEVC/SCAL scal=-",
+            "enter DEVC/STRM",
+            "value DEVC/STRM/SCAL scal=10",
+            "value DEVC/STRM/ACCL scal=20",
+            "leave DEVC/STRM",
+            "value DEVC/GYRO scal=10",
+            "leave DEVC",
+        ]);
+    }
+}
@@ -0,0 +1,168 @@
+//! Differences in telemetry structure between two files, reported by the
+//! `diff` subcommand.
+
+use gpmf_util::summary::{DeviceSummary, StreamSummary, TrackSummary};
+
+/// How far values may drift before [`diff_summaries`] reports them.
+#[derive(Debug, Clone, Copy)]
+pub struct DiffOptions {
+    /// Largest relative rate change that isn't reported, e.g. `0.05` for 5%.
+    pub rate_tolerance: f64,
+    /// Largest move of a range bound that isn't reported, relative to the
+    /// largest bound of both ranges.
+    pub range_tolerance: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change {
+    Added,
+    Removed,
+    Changed {
+        /// What changed, e.g. `SCAL` or `range of element 0`.
+        field: String,
+        before: String,
+        after: String,
+    },
+}
+
+/// A device or stream that differs between the two files.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Difference {
+    /// `DVID`.
+    pub device_id: Option<u32>,
+    /// `DVNM`.
+    pub device_name: Option<String>,
+    /// FourCC of the stream's data KLV. `None` for the device itself.
+    pub stream: Option<String>,
+    pub change: Change,
+}
+
+impl std::fmt::Display for Difference {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let sign = match self.change {
+            Change::Added => '+',
+            Change::Removed => '-',
+            Change::Changed { .. } => '~',
+        };
+        write!(f, "{} DEVC {}", sign, self.device_id.map_or("-".to_string(), |id| id.to_string()))?;
+        if let Some(name) = &self.device_name {
+            write!(f, " ({})", name)?;
+        }
+        if let Some(stream) = &self.stream {
+            write!(f, " / {}", stream)?;
+        }
+        match &self.change {
+            Change::Added => write!(f, ": added"),
+            Change::Removed => write!(f, ": removed"),
+            Change::Changed { field, before, after } => write!(f, ": {} {} -> {}", field, before, after),
+        }
+    }
+}
+
+impl Difference {
+    pub fn to_json(&self) -> serde_json::Value {
+        let mut json = serde_json::json!({
+            "device_id": self.device_id,
+            "device_name": self.device_name,
+            "stream": self.stream,
+        });
+        match &self.change {
+            Change::Added => json["change"] = "added".into(),
+            Change::Removed => json["change"] = "removed".into(),
+            Change::Changed { field, before, after } => {
+                json["change"] = "changed".into();
+                json["field"] = field.as_str().into();
+                json["before"] = before.as_str().into();
+                json["after"] = after.as_str().into();
+            }
+        }
+        json
+    }
+}
+
+/// Compares the devices and streams of two tracks. Devices are matched by
+/// `DVID` and streams by the FourCC of their data KLV.
+pub fn diff_summaries(a: &TrackSummary, b: &TrackSummary, options: DiffOptions) -> Vec<Difference> {
+    let mut differences = Vec::new();
+    for device_a in &a.devices {
+        match b.devices.iter().find(|device_b| device_b.id == device_a.id) {
+            Some(device_b) => diff_devices(&mut differences, device_a, device_b, options),
+            None => differences.push(device_difference(device_a, None, Change::Removed)),
+        }
+    }
+    for device_b in &b.devices {
+        if !a.devices.iter().any(|device_a| device_a.id == device_b.id) {
+            differences.push(device_difference(device_b, None, Change::Added));
+        }
+    }
+    differences
+}
+
+fn device_difference(device: &DeviceSummary, stream: Option<&StreamSummary>, change: Change) -> Difference {
+    Difference {
+        device_id: device.id,
+        device_name: device.name.clone(),
+        stream: stream.map(|stream| stream.fourcc.as_str().to_string()),
+        change,
+    }
+}
+
+fn diff_devices(differences: &mut Vec<Difference>, a: &DeviceSummary, b: &DeviceSummary, options: DiffOptions) {
+    for stream_a in &a.streams {
+        let Some(stream_b) = b.streams.iter().find(|stream_b| stream_b.fourcc == stream_a.fourcc) else {
+            differences.push(device_difference(a, Some(stream_a), Change::Removed));
+            continue;
+        };
+        let mut changed = |field: &str, before: String, after: String| {
+            differences.push(device_difference(b, Some(stream_b), Change::Changed { field: field.into(), before, after }));
+        };
+
+        let text = |text: &Option<String>| text.as_deref().map_or("-".to_string(), |text| format!("\"{}\"", text));
+        if stream_a.name != stream_b.name {
+            changed("STNM", text(&stream_a.name), text(&stream_b.name));
+        }
+        if stream_a.units != stream_b.units {
+            changed("units", text(&stream_a.units), text(&stream_b.units));
+        }
+        if stream_a.value_type != stream_b.value_type {
+            changed("TYPE", format!("\"{}\"", stream_a.value_type), format!("\"{}\"", stream_b.value_type));
+        }
+        if stream_a.scale != stream_b.scale {
+            changed("SCAL", format_numbers(&stream_a.scale), format_numbers(&stream_b.scale));
+        }
+
+        if let (Some(rate_a), Some(rate_b)) = (stream_a.rate(), stream_b.rate())
+            && (rate_b - rate_a).abs() > options.rate_tolerance * rate_a.max(rate_b)
+        {
+            changed("rate", format!("{:.3} Hz", rate_a), format!("{:.3} Hz", rate_b));
+        }
+
+        if stream_a.ranges.len() != stream_b.ranges.len() {
+            changed("elements", stream_a.ranges.len().to_string(), stream_b.ranges.len().to_string());
+        } else {
+            for (j, (&(min_a, max_a), &(min_b, max_b))) in stream_a.ranges.iter().zip(&stream_b.ranges).enumerate() {
+                let magnitude = [min_a, max_a, min_b, max_b].iter().fold(0.0f64, |max, bound| max.max(bound.abs()));
+                let tolerance = options.range_tolerance * magnitude;
+                if (min_b - min_a).abs() > tolerance || (max_b - max_a).abs() > tolerance {
+                    changed(
+                        &format!("range of element {}", j),
+                        format!("[{:.3}, {:.3}]", min_a, max_a),
+                        format!("[{:.3}, {:.3}]", min_b, max_b),
+                    );
+                }
+            }
+        }
+    }
+    for stream_b in &b.streams {
+        if !a.streams.iter().any(|stream_a| stream_a.fourcc == stream_b.fourcc) {
+            differences.push(device_difference(b, Some(stream_b), Change::Added));
+        }
+    }
+}
+
+fn format_numbers(numbers: &[f64]) -> String {
+    if numbers.is_empty() {
+        return "-".to_string();
+    }
+    numbers.iter().map(|number| number.to_string()).collect::<Vec<_>>().join(",")
+}
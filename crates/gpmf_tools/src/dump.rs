@@ -0,0 +1,161 @@
+//! The KLV tree printed by the `dump` subcommand.
+
+use gpmf_parser::klv::{Fourcc, Header, Klv, Value};
+
+/// A KLV kept by [`DumpFilter`], with the raw bytes of its value.
+#[derive(Debug)]
+pub struct DumpNode<'a> {
+    header: Header,
+    /// `None` for nested KLVs.
+    value: Option<serde_json::Value>,
+    /// The value bytes, without padding.
+    data: &'a [u8],
+    children: Vec<DumpNode<'a>>,
+}
+
+/// Which KLVs [`dump_klvs`] keeps.
+#[derive(Debug, Default)]
+pub struct DumpFilter {
+    /// Keep only KLVs with one of these FourCCs, along with their ancestors and
+    /// descendants. Empty to keep everything.
+    pub fourccs: Vec<Fourcc>,
+}
+
+impl DumpFilter {
+    fn matches(&self, fourcc: Fourcc) -> bool {
+        self.fourccs.is_empty() || self.fourccs.contains(&fourcc)
+    }
+}
+
+/// Builds the tree of `klvs`, which were parsed from `bytes`.
+pub fn dump_klvs<'a>(klvs: &[Klv], bytes: &'a [u8], filter: &DumpFilter) -> Vec<DumpNode<'a>> {
+    let keep_everything = DumpFilter::default();
+    let mut nodes = Vec::new();
+    let mut offset = 0;
+    let mut type_str: Option<&str> = None;
+    for klv in klvs {
+        let tsr = klv.header().tsr();
+        let start = (offset + 8).min(bytes.len());
+        let data = &bytes[start..(start + tsr.data_size()).min(bytes.len())];
+        offset += 8 + tsr.data_size().next_multiple_of(4);
+
+        let node = match klv.value() {
+            Value::Nested(children) => {
+                let keep_all = !filter.fourccs.is_empty() && filter.matches(klv.header().fourcc());
+                let children = dump_klvs(children, data, if keep_all { &keep_everything } else { filter });
+                (filter.matches(klv.header().fourcc()) || !children.is_empty()).then_some(DumpNode {
+                    header: klv.header(),
+                    value: None,
+                    data,
+                    children,
+                })
+            }
+            _ => filter.matches(klv.header().fourcc()).then(|| DumpNode {
+                header: klv.header(),
+                value: Some(value_json(klv, type_str)),
+                data,
+                children: Vec::new(),
+            }),
+        };
+
+        if let (b"TYPE", Value::Ascii(value)) = (klv.header().fourcc().as_bytes(), klv.value()) {
+            type_str = Some(value);
+        }
+        nodes.extend(node);
+    }
+    nodes
+}
+
+/// Writes `nodes` as an indented tree, one KLV per line.
+pub fn write_tree<W: std::io::Write>(writer: &mut W, nodes: &[DumpNode<'_>], depth: usize, hex: bool) -> std::io::Result<()> {
+    for node in nodes {
+        let tsr = node.header.tsr();
+        let indent = "  ".repeat(depth);
+        write!(
+            writer,
+            "{}{} '{}' {}x{}",
+            indent,
+            node.header.fourcc().as_str(),
+            match tsr.value_type().as_char() {
+                '\0' => "\\0".to_string(),
+                c => c.to_string(),
+            },
+            tsr.sample_size(),
+            tsr.repeat(),
+        )?;
+        match &node.value {
+            Some(value) => writeln!(writer, ": {}", value)?,
+            None => writeln!(writer)?,
+        }
+        if hex && node.value.is_some() {
+            for chunk in node.data.chunks(32) {
+                writeln!(writer, "{}  | {}", indent, hex_string(chunk))?;
+            }
+        }
+        write_tree(writer, &node.children, depth + 1, hex)?;
+    }
+    Ok(())
+}
+
+/// `nodes` as a JSON array.
+pub fn tree_json(nodes: &[DumpNode<'_>], hex: bool) -> serde_json::Value {
+    nodes
+        .iter()
+        .map(|node| {
+            let tsr = node.header.tsr();
+            let mut json = serde_json::json!({
+                "fourcc": node.header.fourcc().as_str(),
+                "type": tsr.value_type().as_char().to_string(),
+                "sample_size": tsr.sample_size(),
+                "repeat": tsr.repeat(),
+            });
+            match &node.value {
+                Some(value) => {
+                    json["value"] = value.clone();
+                    if hex {
+                        json["hex"] = hex_string(node.data).into();
+                    }
+                }
+                None => json["children"] = tree_json(&node.children, hex),
+            }
+            json
+        })
+        .collect()
+}
+
+/// The decoded value of a non-nested KLV. Multi-axis samples are grouped into
+/// arrays; `Complex` values are decoded with the preceding `TYPE`, if any.
+fn value_json(klv: &Klv, type_str: Option<&str>) -> serde_json::Value {
+    fn samples<T: Into<serde_json::Value> + Copy>(values: &[T], axis_count: usize) -> serde_json::Value {
+        if axis_count <= 1 {
+            return values.iter().map(|&v| v.into()).collect();
+        }
+        values
+            .chunks(axis_count)
+            .map(|sample| sample.iter().map(|&v| v.into()).collect::<serde_json::Value>())
+            .collect()
+    }
+
+    let axis_count = klv.header().tsr().axis_count();
+    match klv.value() {
+        Value::S8(v) => samples(v, axis_count),
+        Value::U8(v) => samples(v, axis_count),
+        Value::S32(v) => samples(v, axis_count),
+        Value::U32(v) => samples(v, axis_count),
+        Value::F32(v) => samples(v, axis_count),
+        Value::U64(v) => samples(v, axis_count),
+        Value::S16(v) => samples(v, axis_count),
+        Value::U16(v) => samples(v, axis_count),
+        Value::Ascii(text) => text.trim_end_matches('\0').into(),
+        Value::Fourcc(fourccs) => fourccs.iter().map(|f| f.as_str()).collect(),
+        Value::DateTime(raw) => klv.value().as_naive_datetime().map_or_else(|| raw.clone(), |date_time| date_time.to_string()).into(),
+        Value::Complex(value) => type_str
+            .and_then(|type_str| value.structs(type_str).ok())
+            .map_or(serde_json::Value::Null, |structs| structs.into()),
+        Value::Nested(_) => serde_json::Value::Null,
+    }
+}
+
+fn hex_string(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect::<Vec<_>>().join(" ")
+}
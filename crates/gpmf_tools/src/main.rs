@@ -1,12 +1,14 @@
 
+mod diff;
+mod dump;
 mod logging;
+mod parse_demo;
 
-use std::io::Write;
+use std::io::{Read, Seek, SeekFrom, Write};
 use std::fs::File;
 
 use log::trace;
 use clap::{Parser, Subcommand, Args};
-use mp4::{Mp4Reader, FourCC};
 
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
@@ -20,12 +22,30 @@ struct Cli {
 
 #[derive(Subcommand, Debug)]
 enum Commands {
-    #[cfg(all(feature = "gpx", feature = "mp4"))]
+    #[cfg(feature = "gpx")]
     #[command(name = "extract-gpx", about = "Extracts GPS data from a GoPro MP4 file and saves it as a GPX file.")]
     ExtractGpx(ExtractGpxArgs),
+    #[command(name = "select", about = "Prints the KLVs matching a selector (e.g. `DEVC[DVID=1]/STRM[has GPS9]/SCAL`) in each GPMF sample.")]
+    Select(SelectArgs),
+    #[command(name = "hilights", about = "Exports the HiLight tags of a GoPro MP4 file as JSON or as GPX waypoints.")]
+    Hilights(HilightsArgs),
+    #[command(name = "extract-raw", about = "Writes the GPMF payload of each sample to disk, with a JSON index of their MP4 timing.")]
+    ExtractRaw(ExtractRawArgs),
+    #[command(name = "dump", about = "Prints the KLVs of each GPMF sample as an indented tree.")]
+    Dump(DumpArgs),
+    #[command(name = "parse-demo", about = "Prints the GPMF samples in the format of GoPro's `GPMF_ParseDemo`, for diffing against the reference parser.")]
+    ParseDemo(ParseDemoArgs),
+    #[command(name = "info", about = "Summarizes the tracks, devices, streams, GPS coverage and camera metadata of a GoPro file.")]
+    Info(InfoArgs),
+    #[command(name = "diff", about = "Reports added and removed streams and changes of TYPE, SCAL, units, rate and value ranges between two GoPro files.")]
+    Diff(DiffArgs),
+    #[command(name = "validate", about = "Checks every GPMF payload against the GPMF specification and reports errors and warnings with their offsets.")]
+    Validate(ValidateArgs),
+    #[command(name = "camera-info", about = "Prints the camera model, firmware, lens and recording settings stored in a GoPro MP4 file.")]
+    CameraInfo(CameraInfoArgs),
 }
 
-#[cfg(all(feature = "gpx", feature = "mp4"))]
+#[cfg(feature = "gpx")]
 #[derive(Args, Debug)]
 struct ExtractGpxArgs {
     /// The input file to process.
@@ -40,6 +60,163 @@ struct ExtractGpxArgs {
     stdout: bool,
 }
 
+#[derive(Args, Debug)]
+struct SelectArgs {
+    /// The input file to process.
+    #[arg(short='i', long="input")]
+    input_file_path: String,
+    /// The selector to match against the top-level KLVs of each sample.
+    #[arg(short='q', long="query")]
+    query: String,
+    /// Only print matches from the sample with this 0-based index.
+    #[arg(long="sample")]
+    sample_index: Option<usize>,
+}
+
+#[derive(Args, Debug)]
+struct HilightsArgs {
+    /// The input file to process.
+    #[arg(short='i', long="input")]
+    input_file_path: String,
+    /// The output file to write to. Writes to stdout if not specified.
+    #[arg(short='o', long="output")]
+    output_file_path: Option<String>,
+    /// The output format.
+    #[arg(short='f', long="format", value_enum, default_value_t = HilightsFormat::Json)]
+    format: HilightsFormat,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug)]
+enum HilightsFormat {
+    /// An array of `{ "time", "latitude", "longitude", "altitude" }` objects, with `null` positions when there is no GPS fix.
+    Json,
+    /// GPX waypoints. HiLights without a GPS fix are skipped.
+    #[cfg(feature = "gpx")]
+    Gpx,
+}
+
+#[derive(Args, Debug)]
+struct ExtractRawArgs {
+    /// The input file to process.
+    #[arg(short='i', long="input")]
+    input_file_path: String,
+    /// The directory to write one `sample_NNNNNN.bin` per sample and `index.json` to, or with `--single-file`, the
+    /// file to write all payloads to.
+    #[arg(short='o', long="output")]
+    output_path: String,
+    /// Write all payloads to a single file, each preceded by its length as a big-endian 32-bit integer.
+    #[arg(long="single-file", default_value_t = false)]
+    single_file: bool,
+    /// Where to write the JSON index. Defaults to `index.json` in the output directory, or the output file with a
+    /// `.json` extension appended.
+    #[arg(long="index")]
+    index_file_path: Option<String>,
+}
+
+#[derive(Args, Debug)]
+struct DumpArgs {
+    /// The input file to process.
+    #[arg(short='i', long="input")]
+    input_file_path: String,
+    /// Only print KLVs with this FourCC, along with their enclosing and nested KLVs. Can be repeated.
+    #[arg(short='k', long="fourcc", value_parser = parse_fourcc)]
+    fourccs: Vec<gpmf_parser::klv::Fourcc>,
+    /// Only print the samples in this 0-based range: `N`, `N..M` (excluding `M`), `N..` or `..M`.
+    #[arg(long="samples", value_parser = parse_sample_range)]
+    sample_range: Option<std::ops::Range<usize>>,
+    /// Also print the raw bytes of each value, in hex.
+    #[arg(long="hex", default_value_t = false)]
+    hex: bool,
+    /// Print JSON instead of a tree.
+    #[arg(long="json", default_value_t = false)]
+    json: bool,
+}
+
+fn parse_fourcc(s: &str) -> Result<gpmf_parser::klv::Fourcc, String> {
+    let bytes: [u8; 4] = s.as_bytes().try_into().map_err(|_| format!("`{}` is not a FourCC", s))?;
+    Ok(gpmf_parser::klv::Fourcc(bytes))
+}
+
+fn parse_sample_range(s: &str) -> Result<std::ops::Range<usize>, String> {
+    let index = |s: &str| s.parse::<usize>().map_err(|e| format!("Invalid sample index `{}`: {}", s, e));
+    match s.split_once("..") {
+        Some((start, end)) => Ok(std::ops::Range {
+            start: if start.is_empty() { 0 } else { index(start)? },
+            end: if end.is_empty() { usize::MAX } else { index(end)? },
+        }),
+        None => {
+            let index = index(s)?;
+            Ok(index..index + 1)
+        }
+    }
+}
+
+#[derive(Args, Debug)]
+struct ParseDemoArgs {
+    /// The input file to process.
+    #[arg(short='i', long="input")]
+    input_file_path: String,
+    /// Print every payload instead of only the first, like the demo's `-a`.
+    #[arg(short='a', long="all-payloads", default_value_t = false)]
+    all_payloads: bool,
+    /// Print the `PAYLOAD TIME` section.
+    #[arg(long="payload-time", default_value_t = false)]
+    payload_time: bool,
+    /// Print the `PAYLOAD INDEX` section.
+    #[arg(long="payload-index", default_value_t = false)]
+    payload_index: bool,
+    /// Print the `SCALED DATA` section. Printed anyway if no section is selected, as the demo does by default.
+    #[arg(long="scaled-data", default_value_t = false)]
+    scaled_data: bool,
+}
+
+#[derive(Args, Debug)]
+struct InfoArgs {
+    /// The input file to process.
+    #[arg(short='i', long="input")]
+    input_file_path: String,
+    /// Print JSON instead of a table.
+    #[arg(long="json", default_value_t = false)]
+    json: bool,
+}
+
+#[derive(Args, Debug)]
+struct DiffArgs {
+    /// The file to compare from, e.g. recorded with the previous firmware.
+    before_file_path: String,
+    /// The file to compare to.
+    after_file_path: String,
+    /// Largest relative rate change that isn't reported.
+    #[arg(long="rate-tolerance", default_value_t = 0.05)]
+    rate_tolerance: f64,
+    /// Largest move of a value range bound that isn't reported, relative to the largest bound of both ranges.
+    #[arg(long="range-tolerance", default_value_t = 0.5)]
+    range_tolerance: f64,
+    /// Print JSON instead of one line per difference.
+    #[arg(long="json", default_value_t = false)]
+    json: bool,
+    /// Exit with a non-zero code if the files differ, like `git diff --exit-code`.
+    #[arg(long="exit-code", default_value_t = false)]
+    exit_code: bool,
+}
+
+#[derive(Args, Debug)]
+struct ValidateArgs {
+    /// The input file to process.
+    #[arg(short='i', long="input")]
+    input_file_path: String,
+    /// Fail on warnings too.
+    #[arg(long="strict", default_value_t = false)]
+    strict: bool,
+}
+
+#[derive(Args, Debug)]
+struct CameraInfoArgs {
+    /// The input file to process.
+    #[arg(short='i', long="input")]
+    input_file_path: String,
+}
+
 fn main() -> Result<(), String>  {
     let cli = Cli::parse();
 
@@ -52,31 +229,11 @@ fn main() -> Result<(), String>  {
         .apply();
 
     match cli.command {
-        #[cfg(all(feature = "gpx", feature = "mp4"))]
+        #[cfg(feature = "gpx")]
         Commands::ExtractGpx(args) => {
             trace!("Extracting GPX from file: {}", args.input_file_path);
 
-            let in_file = File::open(args.input_file_path)
-                .map_err(|e| format!("Failed to open input file: {}", e))?;
-            let in_file_size = in_file.metadata()
-                .map_err(|e| format!("Failed to get input file size: {}", e))?
-                .len();
-
-            let mut mp4_reader = Mp4Reader::read_header(in_file, in_file_size)
-                .map_err(|e| format!("Failed to read MP4 header: {}", e))?;
-
-            let gpmf_track_id = mp4_reader
-                .tracks()
-                .iter()
-                .find(|&(_, track)| {
-                    track.trak.mdia.hdlr.handler_type == FourCC::from(0x6D657461 /* "meta" */)
-                        && track.trak.mdia.hdlr.name.contains("GoPro MET")
-                })
-                .map(|(track_id, _)| *track_id);
-            let gpmf_track_id = gpmf_track_id.ok_or_else(|| "No GPMF track found in the MP4 file".to_string())?;
-
-            let gpmf_track = gpmf_util::GpmfTrack::from_mp4_reader(&mut mp4_reader, gpmf_track_id)
-                .map_err(|e| format!("Failed to read GPMF track: {}", e))?;
+            let gpmf_track = read_gpmf_track(&args.input_file_path)?;
 
             trace!("GPMF sample count: {}", gpmf_track.gpmf_sample_infos().len());
 
@@ -95,5 +252,517 @@ fn main() -> Result<(), String>  {
 
             Ok(())
         }
+        Commands::Select(args) => {
+            let selector = gpmf_parser::Selector::parse(&args.query)
+                .map_err(|e| format!("Invalid selector: {}", e))?;
+
+            let gpmf_track = read_gpmf_track(&args.input_file_path)?;
+
+            for (sample_index, sample_info) in gpmf_track.gpmf_sample_infos().iter().enumerate() {
+                if args.sample_index.is_some_and(|i| i != sample_index) {
+                    continue;
+                }
+                for klv in selector.select(&sample_info.klvs()) {
+                    println!("[{}] {:?} {:?}", sample_index, klv.header(), klv.value());
+                }
+            }
+
+            Ok(())
+        }
+        Commands::Hilights(args) => {
+            let gpmf_track = read_gpmf_track(&args.input_file_path)?;
+
+            trace!("HiLight count: {}", gpmf_track.hilights().len());
+
+            let mut writer: Box<dyn Write> = if let Some(output_file_path) = args.output_file_path {
+                Box::new(File::create(output_file_path).map_err(|e| format!("Failed to create output file: {}", e))?)
+            } else {
+                Box::new(std::io::stdout())
+            };
+
+            match args.format {
+                HilightsFormat::Json => {
+                    let hilights: Vec<serde_json::Value> = gpmf_track
+                        .hilights_with_gps9()
+                        .iter()
+                        .map(|hilight| {
+                            let gps9 = hilight.gps9.as_ref();
+                            serde_json::json!({
+                                "time": hilight.time,
+                                "latitude": gps9.map(|gps9| gps9.latitude),
+                                "longitude": gps9.map(|gps9| gps9.longitude),
+                                "altitude": gps9.map(|gps9| gps9.altitude),
+                            })
+                        })
+                        .collect();
+                    serde_json::to_writer_pretty(&mut writer, &hilights)
+                        .map_err(|e| format!("Failed to write JSON: {}", e))?;
+                    writeln!(writer).map_err(|e| format!("Failed to write JSON: {}", e))?;
+                }
+                #[cfg(feature = "gpx")]
+                HilightsFormat::Gpx => {
+                    gpmf_track.write_hilights_gpx(&mut writer)
+                        .map_err(|e| format!("Failed to write GPX: {}", e))?;
+                }
+            }
+
+            Ok(())
+        }
+        Commands::ExtractRaw(args) => {
+            let gpmf_track = read_gpmf_track(&args.input_file_path)?;
+
+            trace!("GPMF sample count: {}", gpmf_track.gpmf_sample_infos().len());
+
+            let index = if args.single_file {
+                extract_raw_single_file(&gpmf_track, &args.output_path)?
+            } else {
+                extract_raw_files(&gpmf_track, &args.output_path)?
+            };
+
+            let index_file_path = args.index_file_path.unwrap_or_else(|| {
+                if args.single_file {
+                    format!("{}.json", args.output_path)
+                } else {
+                    std::path::Path::new(&args.output_path).join("index.json").to_string_lossy().into_owned()
+                }
+            });
+            trace!("Writing index to file: {}", index_file_path);
+            let mut index_file = File::create(&index_file_path)
+                .map_err(|e| format!("Failed to create index file: {}", e))?;
+            serde_json::to_writer_pretty(&mut index_file, &index)
+                .map_err(|e| format!("Failed to write JSON: {}", e))?;
+            writeln!(index_file).map_err(|e| format!("Failed to write JSON: {}", e))?;
+
+            Ok(())
+        }
+        Commands::Dump(args) => {
+            let gpmf_track = read_gpmf_track(&args.input_file_path)?;
+            let filter = dump::DumpFilter { fourccs: args.fourccs };
+
+            let mut stdout = std::io::stdout().lock();
+            let mut samples = Vec::new();
+            for (sample_index, sample_info) in gpmf_track.gpmf_sample_infos().iter().enumerate() {
+                if args.sample_range.as_ref().is_some_and(|range| !range.contains(&sample_index)) {
+                    continue;
+                }
+                let nodes = dump::dump_klvs(&sample_info.klvs(), sample_info.bytes(), &filter);
+
+                if args.json {
+                    samples.push(serde_json::json!({
+                        "sample": sample_index,
+                        "time_range": sample_info.time_range(),
+                        "klvs": dump::tree_json(&nodes, args.hex),
+                    }));
+                    continue;
+                }
+                let result = match sample_info.time_range() {
+                    Some((start, end)) => writeln!(stdout, "Sample {} ({:.3} s - {:.3} s)", sample_index, start, end),
+                    None => writeln!(stdout, "Sample {}", sample_index),
+                };
+                result
+                    .and_then(|_| dump::write_tree(&mut stdout, &nodes, 1, args.hex))
+                    .map_err(|e| format!("Failed to write output: {}", e))?;
+            }
+
+            if args.json {
+                serde_json::to_writer_pretty(&mut stdout, &samples)
+                    .map_err(|e| format!("Failed to write JSON: {}", e))?;
+                writeln!(stdout).map_err(|e| format!("Failed to write JSON: {}", e))?;
+            }
+
+            Ok(())
+        }
+        Commands::ParseDemo(args) => {
+            let gpmf_track = read_gpmf_track(&args.input_file_path)?;
+
+            let options = parse_demo::ParseDemoOptions {
+                all_payloads: args.all_payloads,
+                payload_time: args.payload_time,
+                payload_index: args.payload_index,
+                scaled_data: args.scaled_data || !(args.payload_time || args.payload_index),
+            };
+            parse_demo::write_parse_demo(&mut std::io::stdout().lock(), &gpmf_track, options)
+                .map_err(|e| format!("Failed to write output: {}", e))?;
+
+            Ok(())
+        }
+        Commands::Info(args) => {
+            let gpmf_track = read_gpmf_track(&args.input_file_path)?;
+            let mp4_tracks = gpmf_track.mp4_tracks();
+            let summary = gpmf_track.summary();
+
+            if args.json {
+                let json = info_json(mp4_tracks, &summary, gpmf_track.camera_info());
+                serde_json::to_writer_pretty(std::io::stdout(), &json)
+                    .map_err(|e| format!("Failed to write JSON: {}", e))?;
+                println!();
+            } else {
+                print_info(mp4_tracks, &summary, gpmf_track.camera_info());
+            }
+
+            Ok(())
+        }
+        Commands::Diff(args) => {
+            let before = read_gpmf_track(&args.before_file_path)?.summary();
+            let after = read_gpmf_track(&args.after_file_path)?.summary();
+            let options = diff::DiffOptions {
+                rate_tolerance: args.rate_tolerance,
+                range_tolerance: args.range_tolerance,
+            };
+            let differences = diff::diff_summaries(&before, &after, options);
+
+            if args.json {
+                let json: Vec<serde_json::Value> = differences.iter().map(diff::Difference::to_json).collect();
+                serde_json::to_writer_pretty(std::io::stdout(), &json)
+                    .map_err(|e| format!("Failed to write JSON: {}", e))?;
+                println!();
+            } else {
+                for difference in &differences {
+                    println!("{}", difference);
+                }
+            }
+
+            if args.exit_code && !differences.is_empty() {
+                return Err(format!("The files differ in {} places", differences.len()));
+            }
+            Ok(())
+        }
+        Commands::Validate(args) => {
+            let mut validator = gpmf_parser::validate::Validator::new();
+            let (mut index, mut errors, mut warnings) = (0, 0, 0);
+            for_each_raw_payload(&args.input_file_path, |payload| {
+                for issue in validator.validate_payload(payload) {
+                    match issue.severity {
+                        gpmf_parser::validate::Severity::Error => errors += 1,
+                        gpmf_parser::validate::Severity::Warning => warnings += 1,
+                    }
+                    println!("payload {}: {}", index, issue);
+                }
+                index += 1;
+            })?;
+            println!("{} errors, {} warnings in {} payloads", errors, warnings, index);
+
+            if errors > 0 || (args.strict && warnings > 0) {
+                return Err("The GPMF payloads do not conform to the specification".to_string());
+            }
+            Ok(())
+        }
+        Commands::CameraInfo(args) => {
+            let mut in_file = File::open(&args.input_file_path)
+                .map_err(|e| format!("Failed to open input file: {}", e))?;
+            if InputFormat::sniff(&mut in_file)? != InputFormat::Mp4 {
+                return Err("Camera metadata is only stored in MP4 files".to_string());
+            }
+            let camera_info = gpmf_util::camera_info::CameraInfo::from_mp4_stream(&mut in_file)?
+                .ok_or_else(|| "No camera metadata (udta GPMF) found in the MP4 file".to_string())?;
+
+            print_camera_info(&camera_info);
+
+            Ok(())
+        }
+    }
+}
+
+/// Writes each payload of `gpmf_track` to its own file in `output_dir`, and
+/// returns the index entries.
+fn extract_raw_files(gpmf_track: &gpmf_util::GpmfTrack, output_dir: &str) -> Result<Vec<serde_json::Value>, String> {
+    std::fs::create_dir_all(output_dir)
+        .map_err(|e| format!("Failed to create output directory: {}", e))?;
+
+    let mut index = Vec::with_capacity(gpmf_track.gpmf_sample_infos().len());
+    for (sample_index, sample_info) in gpmf_track.gpmf_sample_infos().iter().enumerate() {
+        let file_name = format!("sample_{:06}.bin", sample_index);
+        std::fs::write(std::path::Path::new(output_dir).join(&file_name), sample_info.bytes())
+            .map_err(|e| format!("Failed to write {}: {}", file_name, e))?;
+
+        let mut entry = raw_index_entry(sample_index, sample_info);
+        entry["file"] = file_name.into();
+        index.push(entry);
+    }
+    Ok(index)
+}
+
+/// Writes the payloads of `gpmf_track` to `output_file_path` as a
+/// length-prefixed sequence, and returns the index entries.
+fn extract_raw_single_file(gpmf_track: &gpmf_util::GpmfTrack, output_file_path: &str) -> Result<Vec<serde_json::Value>, String> {
+    let out_file = File::create(output_file_path)
+        .map_err(|e| format!("Failed to create output file: {}", e))?;
+    let mut writer = std::io::BufWriter::new(out_file);
+
+    let mut index = Vec::with_capacity(gpmf_track.gpmf_sample_infos().len());
+    let mut offset = 0;
+    for (sample_index, sample_info) in gpmf_track.gpmf_sample_infos().iter().enumerate() {
+        let bytes = sample_info.bytes();
+        writer.write_all(&(bytes.len() as u32).to_be_bytes())
+            .and_then(|_| writer.write_all(bytes))
+            .map_err(|e| format!("Failed to write output file: {}", e))?;
+
+        let mut entry = raw_index_entry(sample_index, sample_info);
+        entry["offset"] = (offset + 4).into();
+        index.push(entry);
+        offset += 4 + bytes.len();
+    }
+    writer.flush().map_err(|e| format!("Failed to write output file: {}", e))?;
+    Ok(index)
+}
+
+/// Index entry of one payload. The MP4 timing fields are `null` for inputs
+/// that aren't MP4 files.
+fn raw_index_entry(sample_index: usize, sample_info: &gpmf_util::GpmfSampleInfo) -> serde_json::Value {
+    let mp4_sample_info = sample_info.mp4_sample_info();
+    serde_json::json!({
+        "sample": sample_index,
+        "size": sample_info.bytes().len(),
+        "start_time": mp4_sample_info.map(|info| info.start_time),
+        "duration": mp4_sample_info.map(|info| info.duration),
+        "timescale": mp4_sample_info.map(|info| info.timescale),
+        "rendering_offset": mp4_sample_info.map(|info| info.rendering_offset),
+        "is_sync": mp4_sample_info.map(|info| info.is_sync),
+    })
+}
+
+/// Prints the `info` summary as a table.
+fn print_info(
+    mp4_tracks: &[gpmf_util::summary::Mp4TrackSummary],
+    summary: &gpmf_util::summary::TrackSummary,
+    camera_info: Option<&gpmf_util::camera_info::CameraInfo>,
+) {
+    if !mp4_tracks.is_empty() {
+        println!("MP4 tracks:");
+        for track in mp4_tracks {
+            println!(
+                "  #{:<3} {:<4}  {:<24} {:>8} samples {:>10.3} s{}",
+                track.track_id,
+                track.handler_type,
+                track.handler_name,
+                track.sample_count,
+                track.duration,
+                if track.is_gpmf { "  (GPMF)" } else { "" },
+            );
+        }
+        println!();
+    }
+
+    match summary.duration {
+        Some(duration) => println!("GPMF samples: {} ({:.3} s)", summary.sample_count, duration),
+        None => println!("GPMF samples: {}", summary.sample_count),
+    }
+    for device in &summary.devices {
+        println!();
+        println!(
+            "Device {}: {}",
+            device.id.map_or("?".to_string(), |id| id.to_string()),
+            device.name.as_deref().unwrap_or("(unnamed)"),
+        );
+        println!("  {:<4}  {:<40} {:<12} {:<12} {:>10} {:>10}", "Key", "Name", "Units", "Type", "Samples", "Rate (Hz)");
+        for stream in &device.streams {
+            println!(
+                "  {:<4}  {:<40} {:<12} {:<12} {:>10} {:>10}",
+                stream.fourcc.as_str(),
+                stream.name.as_deref().unwrap_or("-"),
+                stream.units.as_deref().unwrap_or("-"),
+                stream.value_type,
+                stream.sample_count,
+                stream.rate().map_or("-".to_string(), |rate| format!("{:.3}", rate)),
+            );
+        }
+    }
+
+    println!();
+    let gps = &summary.gps;
+    if gps.sample_count == 0 {
+        println!("GPS: no GPS9 stream");
+    } else {
+        println!(
+            "GPS: fix in {} of {} samples ({:.1}%)",
+            gps.fix_count,
+            gps.sample_count,
+            gps.coverage() * 100.0,
+        );
+        if let (Some(first), Some(last)) = (gps_time(gps.first_fix.as_ref()), gps_time(gps.last_fix.as_ref())) {
+            println!("GPS time: {} to {}", first, last);
+        }
+    }
+
+    if let Some(camera_info) = camera_info {
+        println!();
+        println!("Camera:");
+        for (name, value) in camera_info_fields(camera_info) {
+            println!("  {}: {}", name, value);
+        }
+    }
+}
+
+/// The `info` summary as JSON.
+fn info_json(
+    mp4_tracks: &[gpmf_util::summary::Mp4TrackSummary],
+    summary: &gpmf_util::summary::TrackSummary,
+    camera_info: Option<&gpmf_util::camera_info::CameraInfo>,
+) -> serde_json::Value {
+    let tracks: Vec<serde_json::Value> = mp4_tracks
+        .iter()
+        .map(|track| serde_json::json!({
+            "track_id": track.track_id,
+            "handler_type": track.handler_type,
+            "handler_name": track.handler_name,
+            "sample_count": track.sample_count,
+            "duration": track.duration,
+            "is_gpmf": track.is_gpmf,
+        }))
+        .collect();
+    let devices: Vec<serde_json::Value> = summary
+        .devices
+        .iter()
+        .map(|device| serde_json::json!({
+            "id": device.id,
+            "name": device.name,
+            "streams": device.streams.iter().map(|stream| serde_json::json!({
+                "fourcc": stream.fourcc.as_str(),
+                "name": stream.name,
+                "units": stream.units,
+                "type": stream.value_type,
+                "sample_count": stream.sample_count,
+                "rate": stream.rate(),
+            })).collect::<Vec<_>>(),
+        }))
+        .collect();
+    let gps = &summary.gps;
+
+    serde_json::json!({
+        "tracks": tracks,
+        "sample_count": summary.sample_count,
+        "duration": summary.duration,
+        "devices": devices,
+        "gps": {
+            "sample_count": gps.sample_count,
+            "fix_count": gps.fix_count,
+            "coverage": gps.coverage(),
+            "first_time": gps_time(gps.first_fix.as_ref()),
+            "last_time": gps_time(gps.last_fix.as_ref()),
+        },
+        "camera": camera_info.map(|camera_info| serde_json::json!({
+            "model": camera_info.model,
+            "firmware": camera_info.firmware,
+            "serial_number": camera_info.serial_number,
+            "camera_id": camera_info.camera_id,
+            "lens": camera_info.lens,
+            "projection": camera_info.projection,
+            "resolution": camera_info.resolution,
+            "frame_rate": camera_info.fps(),
+        })),
+    })
+}
+
+/// RFC 3339 time of a GPS fix.
+fn gps_time(gps9: Option<&gpmf_parser::Gps9>) -> Option<String> {
+    gps9?.to_datetime()?.format(&time::format_description::well_known::Rfc3339).ok()
+}
+
+fn print_camera_info(camera_info: &gpmf_util::camera_info::CameraInfo) {
+    for (name, value) in camera_info_fields(camera_info) {
+        println!("{}: {}", name, value);
+    }
+}
+
+/// The fields of `camera_info` that are present, with their display names.
+fn camera_info_fields(camera_info: &gpmf_util::camera_info::CameraInfo) -> Vec<(&'static str, String)> {
+    let fields = [
+        ("Model", camera_info.model.clone()),
+        ("Firmware", camera_info.firmware.clone()),
+        ("Serial number", camera_info.serial_number.clone()),
+        ("Camera ID", camera_info.camera_id.clone()),
+        ("Lens", camera_info.lens.clone()),
+        ("Projection", camera_info.projection.clone()),
+        ("Resolution", camera_info.resolution.map(|(width, height)| format!("{}x{}", width, height))),
+        ("Frame rate", camera_info.fps().map(|fps| format!("{:.3} fps", fps))),
+    ];
+    fields.into_iter().filter_map(|(name, value)| Some((name, value?))).collect()
+}
+
+/// Kinds of input file, told apart by their first bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum InputFormat {
+    Mp4,
+    /// A GoPro photo with GPMF in its APP6 segments.
+    Jpeg,
+    /// A raw GPMF dump, as a single payload or a length-prefixed sequence.
+    RawGpmf,
+}
+
+impl InputFormat {
+    /// Sniffs the format of `file` and rewinds it. Anything that isn't a JPEG
+    /// or raw GPMF is assumed to be an MP4.
+    fn sniff(file: &mut File) -> Result<Self, String> {
+        let mut magic = Vec::with_capacity(8);
+        Read::by_ref(file).take(8).read_to_end(&mut magic)
+            .map_err(|e| format!("Failed to read input file: {}", e))?;
+        file.seek(SeekFrom::Start(0))
+            .map_err(|e| format!("Failed to seek input file: {}", e))?;
+
+        Ok(if gpmf_util::jpeg::is_jpeg(&magic) {
+            Self::Jpeg
+        } else if gpmf_util::raw::is_raw_gpmf(&magic) {
+            Self::RawGpmf
+        } else {
+            Self::Mp4
+        })
+    }
+}
+
+/// Hands each GPMF payload of the file at `input_file_path` to `f` without
+/// parsing it. MP4 samples are read one at a time; JPEG photos have a single
+/// payload.
+fn for_each_raw_payload(input_file_path: &str, mut f: impl FnMut(&[u8])) -> Result<(), String> {
+    let mut in_file = File::open(input_file_path)
+        .map_err(|e| format!("Failed to open input file: {}", e))?;
+
+    let format = InputFormat::sniff(&mut in_file)?;
+    if format == InputFormat::Mp4 {
+        return gpmf_util::raw::for_each_mp4_payload(&mut in_file, f).map(|_| ());
+    }
+    let mut bytes = Vec::new();
+    in_file.read_to_end(&mut bytes)
+        .map_err(|e| format!("Failed to read input file: {}", e))?;
+    match format {
+        InputFormat::Jpeg => {
+            let payload = gpmf_util::jpeg::extract_gpmf(&bytes)
+                .map_err(|e| format!("Failed to read GPMF from JPEG: {}", e))?
+                .ok_or_else(|| "No GPMF found in the JPEG file".to_string())?;
+            f(&payload);
+        }
+        _ => {
+            let payloads = gpmf_util::raw::split_payloads(&bytes)
+                .map_err(|e| format!("Failed to read raw GPMF: {}", e))?;
+            payloads.into_iter().for_each(f);
+        }
+    }
+    Ok(())
+}
+
+/// Reads the GoPro GPMF track of the MP4 file at `input_file_path`, or the
+/// GPMF samples of a GoPro JPEG photo or raw GPMF dump.
+fn read_gpmf_track(input_file_path: &str) -> Result<gpmf_util::GpmfTrack, String> {
+    let mut in_file = File::open(input_file_path)
+        .map_err(|e| format!("Failed to open input file: {}", e))?;
+
+    match InputFormat::sniff(&mut in_file)? {
+        InputFormat::Mp4 => {
+            trace!("Reading the GoPro MET track of the MP4 file");
+            gpmf_util::GpmfTrack::from_mp4_stream(&mut in_file)
+                .map_err(|e| format!("Failed to read GPMF track: {}", e))
+        }
+        InputFormat::Jpeg => {
+            trace!("Reading GPMF from JPEG APP6 segments");
+            gpmf_util::GpmfTrack::from_jpeg_reader(&mut in_file)
+                .map_err(|e| format!("Failed to read GPMF from JPEG: {}", e))
+        }
+        InputFormat::RawGpmf => {
+            trace!("Reading raw GPMF payloads");
+            let mut bytes = Vec::new();
+            in_file.read_to_end(&mut bytes)
+                .map_err(|e| format!("Failed to read input file: {}", e))?;
+            gpmf_util::GpmfTrack::from_raw_gpmf_bytes(&bytes)
+                .map_err(|e| format!("Failed to read raw GPMF: {}", e))
+        }
     }
 }
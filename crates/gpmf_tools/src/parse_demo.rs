@@ -0,0 +1,182 @@
+//! Output in the format of `GPMF_ParseDemo`, the demo program of GoPro's
+//! reference C parser (https://github.com/gopro/gpmf-parser), so the two can
+//! be diffed.
+//!
+//! The `PAYLOAD TIME`, `PAYLOAD INDEX` and `SCALED DATA` sections are
+//! reproduced, including the demo's quirks: trailing spaces, units cut to 7
+//! characters and Latin-1 output.
+
+use std::io::Write;
+
+use gpmf_parser::klv::{Klv, Value, ValueType};
+use gpmf_parser::StreamKlvs;
+
+/// Which sections to print, mirroring the demo's command line flags.
+#[derive(Debug, Clone, Copy)]
+pub struct ParseDemoOptions {
+    /// `-a`: print every payload instead of only the first.
+    pub all_payloads: bool,
+    pub payload_time: bool,
+    pub payload_index: bool,
+    pub scaled_data: bool,
+}
+
+/// The demo's `MAX_UNITLEN - 1`.
+const MAX_UNIT_LEN: usize = 7;
+/// The demo's `MAX_UNITS`.
+const MAX_UNITS: usize = 64;
+
+pub fn write_parse_demo<W: Write>(writer: &mut W, track: &gpmf_util::GpmfTrack, options: ParseDemoOptions) -> std::io::Result<()> {
+    let mut out = String::new();
+    for (index, sample_info) in track.gpmf_sample_infos().iter().enumerate() {
+        if index > 0 && !options.all_payloads {
+            break;
+        }
+        let klvs = sample_info.klvs();
+        let streams = streams(&klvs);
+
+        if options.payload_time {
+            let (start, end) = sample_info.time_range().unwrap_or((0.0, 0.0));
+            out += &format!("PAYLOAD TIME:\n  {:.3} to {:.3} seconds\n", start, end);
+        }
+        if options.payload_index {
+            out += "PAYLOAD INDEX:\n";
+            for stream in &streams {
+                write_index_entry(&mut out, stream);
+            }
+        }
+        if options.scaled_data {
+            out += "SCALED DATA:\n";
+            for stream in &streams {
+                write_scaled_data(&mut out, stream);
+            }
+        }
+    }
+
+    // Strings were decoded from Latin-1, so every char fits in a byte.
+    let bytes: Vec<u8> = out.chars().map(|c| c as u8).collect();
+    writer.write_all(&bytes)
+}
+
+/// Every `STRM` in `klvs`, at any depth, in file order. The sticky KLVs
+/// looked up in them are the nearest preceding ones, like the demo's
+/// `GPMF_FindPrev(..., GPMF_CURRENT_LEVEL)`.
+fn streams(klvs: &[Klv]) -> Vec<StreamKlvs<'_>> {
+    let mut found = Vec::new();
+    for klv in klvs {
+        if let Value::Nested(children) = klv.value() {
+            if klv.header().fourcc().as_bytes() == b"STRM" {
+                found.extend(StreamKlvs::new(children));
+            }
+            found.extend(streams(children));
+        }
+    }
+    found
+}
+
+/// The demo's `GPMF_ElementsInStruct`.
+fn elements_in_struct(stream: &StreamKlvs<'_>) -> usize {
+    let tsr = stream.data().header().tsr();
+    match tsr.value_type() {
+        ValueType::Complex => stream
+            .sticky_text("TYPE")
+            .and_then(|type_str| ValueType::parse_struct_type(type_str).ok())
+            .map_or(tsr.sample_size() as usize, |types| types.len()),
+        _ => tsr.axis_count(),
+    }
+}
+
+fn write_index_entry(out: &mut String, stream: &StreamKlvs<'_>) {
+    let data = stream.data();
+    let key = data.header().fourcc();
+    let samples = stream.sample_count();
+    if samples == 0 {
+        return;
+    }
+
+    *out += &format!("  STRM of {} ", key.as_str());
+    match data.header().tsr().value_type() {
+        ValueType::Complex => {
+            if let Some(type_str) = stream.sticky_text("TYPE") {
+                *out += &format!("of type {} ", type_str);
+            }
+        }
+        value_type => *out += &format!("of type {} ", value_type.as_char()),
+    }
+    *out += &format!("with {} sample{} ", samples, if samples > 1 { "s" } else { "" });
+    let elements = elements_in_struct(stream);
+    if elements > 1 {
+        *out += &format!("-- {} elements per sample", elements);
+    }
+    *out += "\n";
+}
+
+fn write_scaled_data(out: &mut String, stream: &StreamKlvs<'_>) {
+    let data = stream.data();
+    let key = data.header().fourcc();
+    let tsr = data.header().tsr();
+    let samples = tsr.repeat() as usize;
+    let elements = elements_in_struct(stream).max(1);
+
+    let units: Vec<String> = stream
+        .units()
+        .map(|units| {
+            units
+                .iter()
+                .take(MAX_UNITS)
+                .map(|unit| unit.chars().take(MAX_UNIT_LEN).take_while(|&c| c != '\0').collect())
+                .collect()
+        })
+        .filter(|units: &Vec<String>| !units.is_empty())
+        .unwrap_or_else(|| vec![String::new()]);
+    let unit = |j: usize| units[j % units.len()].as_str();
+
+    let scal = stream.sticky("SCAL").and_then(|klv| klv.value().numbers()).unwrap_or_default();
+    let scaled = |j: usize, value: f64| match scal.len() {
+        0 => value,
+        len => value / scal[j % len],
+    };
+
+    match data.value() {
+        Value::Ascii(text) => {
+            let chars: Vec<char> = text.chars().collect();
+            for sample in chars.chunks(elements).take(samples) {
+                *out += &format!("  {} ", key.as_str());
+                out.extend(sample);
+                *out += "\n";
+            }
+        }
+        Value::Complex(value) => {
+            let Some(type_str) = stream.sticky_text("TYPE") else {
+                return;
+            };
+            let (Ok(types), Ok(structs)) = (ValueType::parse_struct_type(type_str), value.structs(type_str)) else {
+                return;
+            };
+            for fields in structs.iter().take(samples) {
+                *out += &format!("  {} ", key.as_str());
+                for (j, (&field, typ)) in fields.iter().zip(&types).enumerate() {
+                    if *typ == ValueType::Fourcc {
+                        out.extend((field as u32).to_be_bytes().map(char::from));
+                        *out += ", ";
+                    } else {
+                        *out += &format!("{:.3}{}, ", scaled(j, field), unit(j));
+                    }
+                }
+                *out += "\n";
+            }
+        }
+        value => {
+            let Some(numbers) = value.numbers() else {
+                return;
+            };
+            for sample in numbers.chunks(elements).take(samples) {
+                *out += &format!("  {} ", key.as_str());
+                for (j, &number) in sample.iter().enumerate() {
+                    *out += &format!("{:.3}{}, ", scaled(j, number), unit(j));
+                }
+                *out += "\n";
+            }
+        }
+    }
+}
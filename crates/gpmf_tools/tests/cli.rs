@@ -0,0 +1,363 @@
+//! Runs the `gpmf_tools` binary against MP4 files written with
+//! `gpmf_util::synth`.
+
+#![cfg(feature = "gpx")]
+
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+use gpmf_util::synth::GoproMp4Builder;
+
+/// A path under the system temp directory, unique to this process and `name`.
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("gpmf_tools-cli-{}-{}", std::process::id(), name))
+}
+
+/// Writes `builder`'s MP4 to a temp file named after the calling test.
+fn write_mp4(name: &str, builder: &GoproMp4Builder) -> PathBuf {
+    let path = temp_path(&format!("{name}.mp4"));
+    builder.write_to(&path).unwrap();
+    path
+}
+
+fn gpmf_tools(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_gpmf_tools"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn extract_gpx_writes_one_point_per_fix() {
+    let input = write_mp4("extract_gpx", &GoproMp4Builder::typical(3));
+    let output = temp_path("extract_gpx.gpx");
+
+    let result = gpmf_tools(&["extract-gpx", "-i", input.to_str().unwrap(), "-o", output.to_str().unwrap()]);
+    assert!(result.status.success(), "stderr: {}", String::from_utf8_lossy(&result.stderr));
+
+    let gpx = std::fs::read_to_string(&output).unwrap();
+    // `GpmfSample::gps9` reports the first fix of each sample.
+    assert_eq!(gpx.matches("<trkpt").count(), 3);
+
+    std::fs::remove_file(input).unwrap();
+    std::fs::remove_file(output).unwrap();
+}
+
+#[test]
+fn extract_gpx_to_stdout() {
+    let input = write_mp4("extract_gpx_stdout", &GoproMp4Builder::typical(2));
+
+    let result = gpmf_tools(&["extract-gpx", "-i", input.to_str().unwrap(), "--stdout"]);
+    assert!(result.status.success());
+    assert_eq!(String::from_utf8_lossy(&result.stdout).matches("<trkpt").count(), 2);
+
+    std::fs::remove_file(input).unwrap();
+}
+
+#[test]
+fn select_prints_matches_per_sample() {
+    let input = write_mp4("select", &GoproMp4Builder::typical(2));
+
+    let result = gpmf_tools(&["select", "-i", input.to_str().unwrap(), "-q", "DEVC/STRM[has ACCL]/SIUN"]);
+    assert!(result.status.success());
+    let stdout = String::from_utf8_lossy(&result.stdout);
+    assert_eq!(stdout.lines().count(), 2);
+    assert!(stdout.lines().all(|line| line.contains("m/s²")));
+
+    let result = gpmf_tools(&["select", "-i", input.to_str().unwrap(), "-q", "DEVC/STRM/GPS9", "--sample", "1"]);
+    assert!(String::from_utf8_lossy(&result.stdout).lines().all(|line| line.starts_with("[1]")));
+
+    std::fs::remove_file(input).unwrap();
+}
+
+#[test]
+fn camera_info_prints_udta_metadata() {
+    use gpmf_parser::synth;
+
+    let udta = [
+        synth::ascii(b"FMWR", "H22.01.01.10.70"),
+        synth::ascii(b"MINF", "HERO11 Black"),
+        synth::u32s(b"VRES", &[3840, 2160]),
+        synth::u32s(b"VFPS", &[60000, 1001]),
+    ]
+    .concat();
+    let input = write_mp4("camera_info", &GoproMp4Builder::typical(1).udta(udta));
+
+    let result = gpmf_tools(&["camera-info", "-i", input.to_str().unwrap()]);
+    assert!(result.status.success(), "stderr: {}", String::from_utf8_lossy(&result.stderr));
+    let stdout = String::from_utf8_lossy(&result.stdout);
+    assert!(stdout.contains("Model: HERO11 Black"));
+    assert!(stdout.contains("Firmware: H22.01.01.10.70"));
+    assert!(stdout.contains("Resolution: 3840x2160"));
+    assert!(stdout.contains("Frame rate: 59.940 fps"));
+
+    std::fs::remove_file(input).unwrap();
+}
+
+#[test]
+fn camera_info_requires_udta() {
+    let input = write_mp4("camera_info_missing", &GoproMp4Builder::typical(1));
+
+    let result = gpmf_tools(&["camera-info", "-i", input.to_str().unwrap()]);
+    assert!(!result.status.success());
+
+    std::fs::remove_file(input).unwrap();
+}
+
+#[test]
+fn hilights_export_as_json_and_gpx() {
+    let input = write_mp4("hilights", &GoproMp4Builder::typical(3).hilights(&[500, 2500]));
+
+    let result = gpmf_tools(&["hilights", "-i", input.to_str().unwrap()]);
+    assert!(result.status.success(), "stderr: {}", String::from_utf8_lossy(&result.stderr));
+    let json: serde_json::Value = serde_json::from_slice(&result.stdout).unwrap();
+    let hilights = json.as_array().unwrap();
+    assert_eq!(hilights.len(), 2);
+    assert_eq!(hilights[1]["time"], 2.5);
+    assert!((hilights[1]["latitude"].as_f64().unwrap() - 35.0002).abs() < 1e-5);
+
+    let result = gpmf_tools(&["hilights", "-i", input.to_str().unwrap(), "--format", "gpx"]);
+    assert!(result.status.success());
+    assert_eq!(String::from_utf8_lossy(&result.stdout).matches("<wpt").count(), 2);
+
+    std::fs::remove_file(input).unwrap();
+}
+
+#[test]
+fn jpeg_input_is_accepted_by_extract_commands() {
+    let input = temp_path("photo.JPG");
+    let gpmf = gpmf_parser::synth::DevcBuilder::typical(0).build();
+    std::fs::write(&input, gpmf_util::synth::gopro_jpeg(&gpmf, 4096)).unwrap();
+
+    let result = gpmf_tools(&["extract-gpx", "-i", input.to_str().unwrap(), "--stdout"]);
+    assert!(result.status.success(), "stderr: {}", String::from_utf8_lossy(&result.stderr));
+    assert_eq!(String::from_utf8_lossy(&result.stdout).matches("<trkpt").count(), 1);
+
+    let result = gpmf_tools(&["select", "-i", input.to_str().unwrap(), "-q", "DEVC/STRM/GPS9"]);
+    assert!(result.status.success());
+    assert_eq!(String::from_utf8_lossy(&result.stdout).lines().count(), 1);
+
+    std::fs::remove_file(input).unwrap();
+}
+
+#[test]
+fn raw_gpmf_input_is_accepted() {
+    use gpmf_parser::synth::DevcBuilder;
+
+    let single = temp_path("single.bin");
+    std::fs::write(&single, DevcBuilder::typical(0).build()).unwrap();
+    let sequence = temp_path("sequence.bin");
+    let payloads: Vec<Vec<u8>> = (0..3).map(|i| DevcBuilder::typical(i).build()).collect();
+    std::fs::write(&sequence, gpmf_util::synth::length_prefixed(&payloads)).unwrap();
+
+    let result = gpmf_tools(&["select", "-i", single.to_str().unwrap(), "-q", "DEVC/STRM/GPS9"]);
+    assert!(result.status.success(), "stderr: {}", String::from_utf8_lossy(&result.stderr));
+    assert_eq!(String::from_utf8_lossy(&result.stdout).lines().count(), 1);
+
+    let result = gpmf_tools(&["extract-gpx", "-i", sequence.to_str().unwrap(), "--stdout"]);
+    assert!(result.status.success(), "stderr: {}", String::from_utf8_lossy(&result.stderr));
+    assert_eq!(String::from_utf8_lossy(&result.stdout).matches("<trkpt").count(), 3);
+
+    let result = gpmf_tools(&["camera-info", "-i", single.to_str().unwrap()]);
+    assert!(!result.status.success());
+
+    std::fs::remove_file(single).unwrap();
+    std::fs::remove_file(sequence).unwrap();
+}
+
+#[test]
+fn extract_raw_writes_payloads_and_index() {
+    use gpmf_parser::synth::DevcBuilder;
+
+    let input = write_mp4("extract_raw", &GoproMp4Builder::typical(2));
+
+    let output_dir = temp_path("extract_raw");
+    let result = gpmf_tools(&["extract-raw", "-i", input.to_str().unwrap(), "-o", output_dir.to_str().unwrap()]);
+    assert!(result.status.success(), "stderr: {}", String::from_utf8_lossy(&result.stderr));
+    assert_eq!(std::fs::read(output_dir.join("sample_000001.bin")).unwrap(), DevcBuilder::typical(1).build());
+    let index: serde_json::Value = serde_json::from_slice(&std::fs::read(output_dir.join("index.json")).unwrap()).unwrap();
+    assert_eq!(index[1]["file"], "sample_000001.bin");
+    assert_eq!(index[1]["start_time"], 1001);
+    assert_eq!(index[1]["duration"], 1001);
+    assert_eq!(index[1]["timescale"], 1000);
+    assert_eq!(index[1]["is_sync"], true);
+
+    // The single file is itself a valid raw GPMF input.
+    let output = temp_path("extract_raw.bin");
+    let result = gpmf_tools(&["extract-raw", "-i", input.to_str().unwrap(), "-o", output.to_str().unwrap(), "--single-file"]);
+    assert!(result.status.success(), "stderr: {}", String::from_utf8_lossy(&result.stderr));
+    let index_path = temp_path("extract_raw.bin.json");
+    let index: serde_json::Value = serde_json::from_slice(&std::fs::read(&index_path).unwrap()).unwrap();
+    let bytes = std::fs::read(&output).unwrap();
+    let (offset, size) = (index[1]["offset"].as_u64().unwrap() as usize, index[1]["size"].as_u64().unwrap() as usize);
+    assert_eq!(&bytes[offset..offset + size], DevcBuilder::typical(1).build());
+
+    let result = gpmf_tools(&["extract-gpx", "-i", output.to_str().unwrap(), "--stdout"]);
+    assert!(result.status.success(), "stderr: {}", String::from_utf8_lossy(&result.stderr));
+    assert_eq!(String::from_utf8_lossy(&result.stdout).matches("<trkpt").count(), 2);
+
+    std::fs::remove_dir_all(output_dir).unwrap();
+    for path in [input, output, index_path] {
+        std::fs::remove_file(path).unwrap();
+    }
+}
+
+#[test]
+fn dump_prints_klv_tree() {
+    let input = write_mp4("dump", &GoproMp4Builder::typical(3));
+
+    let result = gpmf_tools(&["dump", "-i", input.to_str().unwrap()]);
+    assert!(result.status.success(), "stderr: {}", String::from_utf8_lossy(&result.stderr));
+    let stdout = String::from_utf8_lossy(&result.stdout);
+    assert_eq!(stdout.lines().filter(|line| line.starts_with("Sample ")).count(), 3);
+    assert!(stdout.contains("  DEVC '\\0' 1x"), "{stdout}");
+    assert!(stdout.contains("      ACCL 's' 6x200: [["), "{stdout}");
+    assert!(stdout.contains("      STNM 'c' "), "{stdout}");
+
+    let result = gpmf_tools(&["dump", "-i", input.to_str().unwrap(), "-k", "SCAL", "--samples", "1..", "--hex"]);
+    assert!(result.status.success(), "stderr: {}", String::from_utf8_lossy(&result.stderr));
+    let stdout = String::from_utf8_lossy(&result.stdout);
+    assert!(stdout.starts_with("Sample 1 "), "{stdout}");
+    assert!(!stdout.contains("ACCL") && !stdout.contains("STNM"), "{stdout}");
+    assert!(stdout.contains("SCAL 's' 2x1"), "{stdout}");
+    assert!(stdout.contains("| "), "{stdout}");
+
+    let result = gpmf_tools(&["dump", "-i", input.to_str().unwrap(), "--json", "--samples", "2", "-k", "DVNM", "--hex"]);
+    assert!(result.status.success(), "stderr: {}", String::from_utf8_lossy(&result.stderr));
+    let json: serde_json::Value = serde_json::from_slice(&result.stdout).unwrap();
+    let samples = json.as_array().unwrap();
+    assert_eq!(samples.len(), 1);
+    assert_eq!(samples[0]["sample"], 2);
+    let devc = &samples[0]["klvs"][0];
+    assert_eq!(devc["fourcc"], "DEVC");
+    assert_eq!(devc["children"].as_array().unwrap().len(), 1);
+    assert_eq!(devc["children"][0]["value"], "Camera");
+    assert_eq!(devc["children"][0]["hex"], "43 61 6d 65 72 61");
+
+    std::fs::remove_file(input).unwrap();
+}
+
+#[test]
+fn parse_demo_matches_reference_format() {
+    let input = write_mp4("parse_demo", &GoproMp4Builder::typical(2));
+
+    let result = gpmf_tools(&["parse-demo", "-i", input.to_str().unwrap(), "-a", "--payload-time", "--payload-index", "--scaled-data"]);
+    assert!(result.status.success(), "stderr: {}", String::from_utf8_lossy(&result.stderr));
+    let stdout = String::from_utf8_lossy(&result.stdout);
+    assert!(stdout.contains("PAYLOAD TIME:\n  1.001 to 2.002 seconds\nPAYLOAD INDEX:\n"), "{stdout}");
+    assert!(stdout.contains("\n  STRM of ACCL of type s with 200 samples -- 3 elements per sample\n"), "{stdout}");
+    assert!(stdout.contains("\n  STRM of GPS9 of type lllllllSS with 10 samples -- 9 elements per sample\n"), "{stdout}");
+    assert!(stdout.contains("\n  GRAV 0.000, 1.000, 0.000, \n"), "{stdout}");
+    assert_eq!(stdout.matches("SCALED DATA:").count(), 2);
+
+    // Units are written as Latin-1, like the demo does.
+    let result = gpmf_tools(&["parse-demo", "-i", input.to_str().unwrap()]);
+    assert!(result.status.success(), "stderr: {}", String::from_utf8_lossy(&result.stderr));
+    assert!(result.stdout.starts_with(b"SCALED DATA:\n  ACCL 9.811m/s\xb2, 0.000m/s\xb2, 0.000m/s\xb2, \n"));
+    assert_eq!(result.stdout.windows(7).filter(|window| window == b"\n  ACCL").count(), 200);
+
+    std::fs::remove_file(input).unwrap();
+}
+
+#[test]
+fn info_summarizes_tracks_and_streams() {
+    let udta = gpmf_parser::synth::ascii(b"MINF", "HERO11 Black");
+    let input = write_mp4("info", &GoproMp4Builder::typical(3).udta(udta));
+
+    let result = gpmf_tools(&["info", "-i", input.to_str().unwrap()]);
+    assert!(result.status.success(), "stderr: {}", String::from_utf8_lossy(&result.stderr));
+    let stdout = String::from_utf8_lossy(&result.stdout);
+    assert!(stdout.contains("(GPMF)"), "{stdout}");
+    assert!(stdout.contains("Device 1: Camera"), "{stdout}");
+    assert!(stdout.lines().any(|line| line.trim_start().starts_with("ACCL") && line.contains("Accelerometer")), "{stdout}");
+    assert!(stdout.contains("GPS: fix in 3 of 3 samples"), "{stdout}");
+    assert!(stdout.contains("Model: HERO11 Black"), "{stdout}");
+
+    let result = gpmf_tools(&["info", "-i", input.to_str().unwrap(), "--json"]);
+    assert!(result.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&result.stdout).unwrap();
+    assert_eq!(json["tracks"][0]["is_gpmf"], true);
+    assert_eq!(json["sample_count"], 3);
+    let streams = json["devices"][0]["streams"].as_array().unwrap();
+    let gyro = streams.iter().find(|stream| stream["fourcc"] == "GYRO").unwrap();
+    assert_eq!(gyro["sample_count"], 600);
+    assert!((gyro["rate"].as_f64().unwrap() - 199.8).abs() < 0.1);
+    assert_eq!(json["gps"]["coverage"], 1.0);
+    assert!(json["gps"]["first_time"].is_string());
+    assert_eq!(json["camera"]["model"], "HERO11 Black");
+
+    std::fs::remove_file(input).unwrap();
+}
+
+#[test]
+fn missing_input_fails() {
+    let result = gpmf_tools(&["extract-gpx", "-i", temp_path("missing.mp4").to_str().unwrap(), "--stdout"]);
+    assert!(!result.status.success());
+}
+
+#[test]
+fn validate_reports_errors_with_offsets() {
+    use gpmf_parser::synth::DevcBuilder;
+
+    let input = write_mp4("validate", &GoproMp4Builder::typical(3));
+
+    let result = gpmf_tools(&["validate", "-i", input.to_str().unwrap()]);
+    assert!(result.status.success(), "stderr: {}", String::from_utf8_lossy(&result.stderr));
+    assert_eq!(String::from_utf8_lossy(&result.stdout).trim(), "0 errors, 0 warnings in 3 payloads");
+
+    // Payloads out of order: TSMP goes back.
+    let payloads: Vec<Vec<u8>> = [1, 0].map(|i| DevcBuilder::typical(i).build()).to_vec();
+    let reordered = temp_path("validate.bin");
+    std::fs::write(&reordered, gpmf_util::synth::length_prefixed(&payloads)).unwrap();
+
+    let result = gpmf_tools(&["validate", "-i", reordered.to_str().unwrap()]);
+    assert!(!result.status.success());
+    let stdout = String::from_utf8_lossy(&result.stdout);
+    assert!(stdout.lines().any(|line| line.starts_with("payload 1: error at 0x") && line.contains("(DEVC/STRM/TSMP): TSMP went back")), "{stdout}");
+
+    std::fs::remove_file(input).unwrap();
+    std::fs::remove_file(reordered).unwrap();
+}
+
+#[test]
+fn diff_reports_stream_changes() {
+    use gpmf_parser::synth::{self, DevcBuilder, StreamBuilder};
+
+    let before = temp_path("diff_before.bin");
+    std::fs::write(&before, DevcBuilder::typical(0).build()).unwrap();
+    // A firmware that drops GYRO, scales ACCL differently and adds a stream.
+    let after = temp_path("diff_after.bin");
+    let accl = [981i16, 0, 0].iter().flat_map(|v| v.to_be_bytes()).collect::<Vec<u8>>();
+    let devc = DevcBuilder::new(1, "Camera")
+        .stream(
+            StreamBuilder::new()
+                .name("Accelerometer")
+                .units("m/s²")
+                .klv(synth::s16s(b"SCAL", &[100]))
+                .klv(synth::klv(b"ACCL", b's', 6, 1, &accl)),
+        )
+        .stream(StreamBuilder::new().name("Microphone").klv(synth::u16s(b"MWET", &[1])));
+    std::fs::write(&after, devc.build()).unwrap();
+
+    let result = gpmf_tools(&["diff", before.to_str().unwrap(), before.to_str().unwrap(), "--exit-code"]);
+    assert!(result.status.success(), "stderr: {}", String::from_utf8_lossy(&result.stderr));
+    assert!(result.stdout.is_empty());
+
+    let result = gpmf_tools(&["diff", before.to_str().unwrap(), after.to_str().unwrap()]);
+    assert!(result.status.success(), "stderr: {}", String::from_utf8_lossy(&result.stderr));
+    let stdout = String::from_utf8_lossy(&result.stdout);
+    assert!(stdout.lines().any(|line| line == "- DEVC 1 (Camera) / GYRO: removed"), "{stdout}");
+    assert!(stdout.lines().any(|line| line == "+ DEVC 1 (Camera) / MWET: added"), "{stdout}");
+    assert!(stdout.lines().any(|line| line == "~ DEVC 1 (Camera) / ACCL: SCAL 418 -> 100"), "{stdout}");
+    assert!(!stdout.contains("ACCL: range"), "{stdout}");
+
+    let result = gpmf_tools(&["diff", before.to_str().unwrap(), after.to_str().unwrap(), "--json", "--exit-code"]);
+    assert!(!result.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&result.stdout).unwrap();
+    let removed = json.as_array().unwrap().iter().find(|difference| difference["stream"] == "GYRO").unwrap();
+    assert_eq!(removed["change"], "removed");
+    assert_eq!(removed["device_id"], 1);
+
+    std::fs::remove_file(before).unwrap();
+    std::fs::remove_file(after).unwrap();
+}
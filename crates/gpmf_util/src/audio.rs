@@ -0,0 +1,64 @@
+//! Time ranges of audio that needs attention, from the `WNDM` wind and
+//! `MWET` wet-microphone streams.
+
+use crate::segment::{merge_frames, Segment};
+use crate::GpmfTrack;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AudioIssue {
+    /// The camera was suppressing wind noise.
+    WindSuppression,
+    /// At least one microphone was wet.
+    WetMic,
+}
+
+impl GpmfTrack {
+    /// Time ranges in which wind suppression was active or a microphone was
+    /// wet, at least `min_duration` _s_ long, ordered by start. Ranges of
+    /// different issues may overlap.
+    pub fn audio_segments(&self, min_duration: f64) -> Vec<Segment<AudioIssue>> {
+        let wind = self
+            .timed_frames(|sample| sample.wndm())
+            .into_iter()
+            .map(|(start, end, wind)| (start, end, wind.enabled.then_some(AudioIssue::WindSuppression)));
+        let wet = self
+            .timed_frames(|sample| sample.mwet())
+            .into_iter()
+            .map(|(start, end, wet)| (start, end, wet.wet.then_some(AudioIssue::WetMic)));
+
+        let mut segments = merge_frames(wind, min_duration);
+        segments.extend(merge_frames(wet, min_duration));
+        segments.sort_by(|a, b| a.start.total_cmp(&b.start));
+        segments
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use gpmf_parser::audio::{MicWet, Wind};
+    use gpmf_parser::synth::DevcBuilder;
+
+    use crate::tests::track_of;
+
+    #[test]
+    fn wind_and_wet_mic_segments_may_overlap() {
+        let wind = |enabled| Wind { enabled, level: if enabled { 70.0 } else { 0.0 } };
+        let wet = |wet| MicWet { wet, all_mics: false, confidence: 90.0 };
+        let payload = |winds: [bool; 2], wets: [bool; 2]| {
+            DevcBuilder::new(1, "Camera").wndm(&winds.map(wind)).mwet(&wets.map(wet)).build()
+        };
+        let track = track_of(&[payload([false, true], [false, false]), payload([true, false], [true, true])]);
+
+        let segments = track.audio_segments(0.0);
+
+        assert_eq!(segments.len(), 2);
+        assert_eq!(segments[0].kind, AudioIssue::WindSuppression);
+        assert!((segments[0].start - 0.5005).abs() < 1e-9 && (segments[0].end - 1.5015).abs() < 1e-9);
+        assert_eq!(segments[1].kind, AudioIssue::WetMic);
+        assert!((segments[1].start - 1.001).abs() < 1e-9 && (segments[1].end - 2.002).abs() < 1e-9);
+
+        assert!(track.audio_segments(1.5).is_empty());
+    }
+}
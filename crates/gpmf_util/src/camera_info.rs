@@ -0,0 +1,149 @@
+//! Global camera metadata stored as GPMF in the `udta` box of GoPro MP4s.
+
+use gpmf_parser::klv::{Fourcc, Value};
+use gpmf_parser::{Klv, KlvVisitor, StickyScope};
+
+/// Settings and identity of the camera that recorded a file, read from the
+/// `moov/udta/GPMF` box.
+///
+/// Fields are `None` when the camera didn't write the corresponding KLV;
+/// anything not covered by a field is available through
+/// [`CameraInfo::klvs`].
+#[derive(Debug, Clone, Default)]
+pub struct CameraInfo {
+    /// `FMWR`, e.g. `H22.01.01.10.70`.
+    pub firmware: Option<String>,
+    /// `CASN`.
+    pub serial_number: Option<String>,
+    /// `CAME`, the camera's unique ID, as hex.
+    pub camera_id: Option<String>,
+    /// `MINF`, e.g. `HERO11 Black`.
+    pub model: Option<String>,
+    /// `LENS`, e.g. `LAJ8052332302496`.
+    pub lens: Option<String>,
+    /// `PRJT`, e.g. `GPRO` or `EACO`.
+    pub projection: Option<String>,
+    /// `VRES`, width and height in pixels.
+    pub resolution: Option<(u32, u32)>,
+    /// `VFPS`, as a numerator and denominator.
+    pub frame_rate: Option<(u32, u32)>,
+    klvs: Vec<Klv>,
+}
+
+impl CameraInfo {
+    /// Parses the payload of a `udta/GPMF` box.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        let klvs = Klv::from_bytes(bytes).map_err(|e| format!("Failed to parse udta GPMF: {}", e))?;
+        Ok(Self::from_klvs(klvs))
+    }
+
+    /// Reads the `udta/GPMF` box of an MP4 stream. `Ok(None)` if it has none.
+    pub fn from_mp4_stream<R: std::io::Read + std::io::Seek>(reader: &mut R) -> Result<Option<Self>, String> {
+        crate::mp4_index::blocking_io::read_moov_index(reader)?.camera_info()
+    }
+
+    /// Picks the known fields out of `klvs`, which may be flat (as written by
+    /// most cameras) or nested in `DEVC`s. The first occurrence of each wins.
+    pub fn from_klvs(klvs: Vec<Klv>) -> Self {
+        struct Collector(CameraInfo);
+
+        impl<'a> KlvVisitor<'a> for Collector {
+            fn visit_value(&mut self, _path: &[Fourcc], klv: &'a Klv, _scope: &StickyScope<'a>) {
+                let info = &mut self.0;
+                let pair = |v: &[u32]| (v.len() >= 2).then(|| (v[0], v[1]));
+                match (klv.header().fourcc().as_bytes(), klv.value()) {
+                    (b"FMWR", value) => set_once(&mut info.firmware, text(value)),
+                    (b"CASN", value) => set_once(&mut info.serial_number, text(value)),
+                    (b"CAME", Value::U8(bytes)) => {
+                        set_once(&mut info.camera_id, Some(bytes.iter().map(|b| format!("{:02X}", b)).collect()))
+                    }
+                    (b"MINF", value) => set_once(&mut info.model, text(value)),
+                    (b"LENS", value) => set_once(&mut info.lens, text(value)),
+                    (b"PRJT", value) => set_once(&mut info.projection, text(value)),
+                    (b"VRES", Value::U32(v)) => set_once(&mut info.resolution, pair(v)),
+                    (b"VFPS", Value::U32(v)) => set_once(&mut info.frame_rate, pair(v)),
+                    _ => {}
+                }
+            }
+        }
+
+        let mut collector = Collector(Self::default());
+        Klv::walk_all(&klvs, &mut collector);
+        Self { klvs, ..collector.0 }
+    }
+
+    /// All KLVs of the `udta/GPMF` box.
+    pub fn klvs(&self) -> &[Klv] {
+        &self.klvs
+    }
+
+    /// [`CameraInfo::frame_rate`] in frames per second.
+    pub fn fps(&self) -> Option<f64> {
+        let (numerator, denominator) = self.frame_rate?;
+        (denominator != 0).then(|| numerator as f64 / denominator as f64)
+    }
+}
+
+fn set_once<T>(field: &mut Option<T>, value: Option<T>) {
+    if field.is_none() {
+        *field = value;
+    }
+}
+
+/// Text of a `c` or `F` KLV, without the NUL padding some cameras write.
+fn text(value: &Value) -> Option<String> {
+    let text = match value {
+        Value::Ascii(s) => s.clone(),
+        Value::Fourcc(fourccs) => fourccs.iter().map(|f| f.as_str()).collect(),
+        _ => return None,
+    };
+    let text = text.trim_end_matches('\0').trim();
+    (!text.is_empty()).then(|| text.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use gpmf_parser::synth;
+
+    /// A `udta/GPMF` payload shaped like a HERO11's.
+    fn hero11_udta() -> Vec<u8> {
+        [
+            synth::ascii(b"FMWR", "H22.01.01.10.70"),
+            synth::ascii(b"LENS", "LAJ8052332302496"),
+            synth::klv(b"CAME", b'B', 4, 1, &[0xDE, 0xAD, 0xBE, 0xEF]),
+            synth::ascii(b"CASN", "C3471325208909\0\0"),
+            synth::ascii(b"MINF", "HERO11 Black"),
+            synth::klv(b"PRJT", b'F', 4, 1, b"GPRO"),
+            synth::u32s(b"VRES", &[3840, 2160]),
+            synth::u32s(b"VFPS", &[60000, 1001]),
+            synth::u32s(b"EISA", &[1]),
+        ]
+        .concat()
+    }
+
+    #[test]
+    fn known_fields_are_parsed() {
+        let info = CameraInfo::from_bytes(&hero11_udta()).unwrap();
+
+        assert_eq!(info.firmware.as_deref(), Some("H22.01.01.10.70"));
+        assert_eq!(info.serial_number.as_deref(), Some("C3471325208909"));
+        assert_eq!(info.camera_id.as_deref(), Some("DEADBEEF"));
+        assert_eq!(info.model.as_deref(), Some("HERO11 Black"));
+        assert_eq!(info.lens.as_deref(), Some("LAJ8052332302496"));
+        assert_eq!(info.projection.as_deref(), Some("GPRO"));
+        assert_eq!(info.resolution, Some((3840, 2160)));
+        assert!((info.fps().unwrap() - 59.94).abs() < 1e-2);
+        assert_eq!(info.klvs().len(), 9);
+    }
+
+    #[test]
+    fn nested_fields_are_found() {
+        let bytes = synth::nested(b"DEVC", &[synth::ascii(b"MINF", "HERO9 Black")]);
+        let info = CameraInfo::from_bytes(&bytes).unwrap();
+
+        assert_eq!(info.model.as_deref(), Some("HERO9 Black"));
+        assert!(info.firmware.is_none());
+    }
+}
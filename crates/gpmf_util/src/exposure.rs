@@ -0,0 +1,119 @@
+//! Detection of unusable footage from the `YAVG` luma and `UNIF` uniformity
+//! streams.
+
+use crate::segment::{merge_frames, Segment};
+use crate::GpmfTrack;
+
+/// Why a frame is considered unusable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ExposureIssue {
+    /// Average luma below [`ExposureThresholds::dark_below`].
+    Dark,
+    /// Average luma above [`ExposureThresholds::blown_out_above`].
+    BlownOut,
+    /// A flat, non-blown-out image, as when something covers the lens.
+    LensCovered,
+}
+
+/// Criteria for [`GpmfTrack::exposure_segments`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ExposureThresholds {
+    /// Average luma, in `[0, 255]`, below which a frame is dark.
+    pub dark_below: f32,
+    /// Average luma, in `[0, 255]`, above which a frame is blown out.
+    pub blown_out_above: f32,
+    /// Uniformity, in `[0, 1]`, from which a frame counts as lens-covered.
+    pub covered_uniformity: f32,
+    /// Segments shorter than this, in _s_, are ignored.
+    pub min_duration: f64,
+}
+
+impl Default for ExposureThresholds {
+    fn default() -> Self {
+        Self {
+            dark_below: 16.0,
+            blown_out_above: 240.0,
+            covered_uniformity: 0.95,
+            min_duration: 0.5,
+        }
+    }
+}
+
+impl ExposureThresholds {
+    /// Classifies a frame. `uniformity` is `None` when there is no `UNIF`
+    /// stream.
+    pub fn classify(&self, luma: f32, uniformity: Option<f32>) -> Option<ExposureIssue> {
+        if luma > self.blown_out_above {
+            Some(ExposureIssue::BlownOut)
+        } else if uniformity.is_some_and(|u| u >= self.covered_uniformity) {
+            Some(ExposureIssue::LensCovered)
+        } else if luma < self.dark_below {
+            Some(ExposureIssue::Dark)
+        } else {
+            None
+        }
+    }
+}
+
+impl GpmfTrack {
+    /// Time ranges in which the footage is dark, blown out or shot through a
+    /// covered lens, in chronological order.
+    ///
+    /// Frames are those of the `YAVG` stream, each paired with the `UNIF`
+    /// value at the same relative position in its payload. Samples that
+    /// didn't come from an MP4 track are skipped.
+    pub fn exposure_segments(&self, thresholds: &ExposureThresholds) -> Vec<Segment<ExposureIssue>> {
+        let frames = self.timed_frames(|sample| {
+            let (yavg, unif) = (sample.yavg(), sample.unif());
+            yavg.iter()
+                .enumerate()
+                .map(|(i, &luma)| {
+                    let uniformity = (!unif.is_empty()).then(|| unif[i * unif.len() / yavg.len()]);
+                    thresholds.classify(luma, uniformity)
+                })
+                .collect::<Vec<_>>()
+        });
+        merge_frames(frames, thresholds.min_duration)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use gpmf_parser::synth::DevcBuilder;
+
+    use crate::tests::track_of;
+
+    #[test]
+    fn segments_span_consecutive_unusable_frames() {
+        let payload = |yavg: &[u8], unif: &[f32]| DevcBuilder::new(1, "Camera").yavg(yavg).unif(unif).build();
+        // Four frames per ~1 s payload.
+        let track = track_of(&[
+            payload(&[120, 120, 5, 5], &[0.2, 0.2, 0.5, 0.5]),
+            payload(&[5, 5, 30, 30], &[0.5, 0.99, 0.99, 0.99]),
+            payload(&[250, 120, 120, 120], &[0.2, 0.2, 0.2, 0.2]),
+        ]);
+
+        let segments = track.exposure_segments(&ExposureThresholds { min_duration: 0.0, ..Default::default() });
+
+        let kinds: Vec<_> = segments.iter().map(|s| s.kind).collect();
+        assert_eq!(kinds, [ExposureIssue::Dark, ExposureIssue::LensCovered, ExposureIssue::BlownOut]);
+        let step = 1.001 / 4.0;
+        assert!((segments[0].start - 2.0 * step).abs() < 1e-9);
+        assert!((segments[0].end - (1.001 + step)).abs() < 1e-9);
+        assert!((segments[1].duration() - 3.0 * step).abs() < 1e-9);
+        assert!((segments[2].start - 2.002).abs() < 1e-9);
+
+        let segments = track.exposure_segments(&ExposureThresholds::default());
+        assert_eq!(segments.len(), 2, "the single blown-out frame is too short");
+    }
+
+    #[test]
+    fn classify_without_unif_uses_luma_only() {
+        let thresholds = ExposureThresholds::default();
+        assert_eq!(thresholds.classify(3.0, None), Some(ExposureIssue::Dark));
+        assert_eq!(thresholds.classify(3.0, Some(1.0)), Some(ExposureIssue::LensCovered));
+        assert_eq!(thresholds.classify(128.0, None), None);
+    }
+}
@@ -0,0 +1,68 @@
+//! Dropped video frames, from the `MSKP` and `LSKP` streams.
+
+use crate::GpmfTrack;
+
+/// Which video file a [`FrameSkip`] applies to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum VideoStream {
+    /// The main video (`MSKP`).
+    Main,
+    /// The low-resolution proxy (`LSKP`).
+    LowRes,
+}
+
+/// A video frame preceded by skipped or duplicated sensor frames.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FrameSkip {
+    pub video: VideoStream,
+    /// Time of the affected frame, in _s_ from the start of the video.
+    pub time: f64,
+    /// Number of frames skipped.
+    pub frames: u32,
+}
+
+impl GpmfTrack {
+    /// Every video frame with a non-zero `MSKP` or `LSKP`, ordered by time.
+    /// Samples that didn't come from an MP4 track are skipped.
+    pub fn frame_skips(&self) -> Vec<FrameSkip> {
+        let mut skips: Vec<FrameSkip> = [
+            (VideoStream::Main, self.timed_frames(|sample| sample.mskp())),
+            (VideoStream::LowRes, self.timed_frames(|sample| sample.lskp())),
+        ]
+        .into_iter()
+        .flat_map(|(video, frames)| {
+            frames
+                .into_iter()
+                .filter(|&(_, _, skipped)| skipped > 0.0)
+                .map(move |(time, _, skipped)| FrameSkip { video, time, frames: skipped as u32 })
+        })
+        .collect();
+        skips.sort_by(|a, b| a.time.total_cmp(&b.time));
+        skips
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use gpmf_parser::synth::DevcBuilder;
+
+    use crate::tests::track_of;
+
+    #[test]
+    fn skips_are_listed_with_video_time() {
+        let track = track_of(&[
+            DevcBuilder::new(1, "Camera").mskp(&[0, 0, 0, 0]).lskp(&[0, 1]).build(),
+            DevcBuilder::new(1, "Camera").mskp(&[0, 0, 3, 0]).lskp(&[0, 0]).build(),
+        ]);
+
+        let skips = track.frame_skips();
+
+        assert_eq!(skips.len(), 2);
+        assert_eq!((skips[0].video, skips[0].frames), (VideoStream::LowRes, 1));
+        assert!((skips[0].time - 0.5005).abs() < 1e-9);
+        assert_eq!((skips[1].video, skips[1].frames), (VideoStream::Main, 3));
+        assert!((skips[1].time - (1.001 + 0.5005)).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,153 @@
+//! HiLight tags, the moments marked by pressing the camera's HiLight button
+//! while recording.
+//!
+//! Older cameras store them in a `udta/HMMT` box, newer ones as an `HMMT` KLV
+//! (nested in `HLMT` on some models) in the `udta/GPMF` box. Both hold times
+//! in _ms_ from the start of the video.
+
+use gpmf_parser::klv::Value;
+use gpmf_parser::{Gps9, Klv};
+
+use crate::camera_info::CameraInfo;
+use crate::GpmfTrack;
+
+/// A HiLight tag with the GPS fix of the payload it falls in.
+#[derive(Debug, Clone, Copy)]
+pub struct Hilight {
+    /// In _s_ from the start of the video.
+    pub time: f64,
+    /// `None` if the payload has no `GPS9` fix.
+    pub gps9: Option<Gps9>,
+}
+
+/// Reads the HiLight times, in _s_, of an MP4 stream.
+pub fn read_hilights<R: std::io::Read + std::io::Seek>(reader: &mut R) -> Result<Vec<f64>, String> {
+    let moov_index = crate::mp4_index::blocking_io::read_moov_index(reader)?;
+    Ok(hilights_of(&moov_index.hmmt, moov_index.camera_info()?.as_ref()))
+}
+
+/// Merges the times of the `udta/HMMT` box, `hmmt`, with those of the `HMMT`
+/// KLVs of the camera metadata, in _s_.
+pub(crate) fn hilights_of(hmmt: &[u32], camera_info: Option<&CameraInfo>) -> Vec<f64> {
+    let mut times_ms: Vec<u32> = hmmt.to_vec();
+    if let Some(camera_info) = camera_info {
+        times_ms.extend(hmmt_klvs(camera_info.klvs()));
+    }
+    times_ms.sort_unstable();
+    times_ms.dedup();
+    times_ms.into_iter().map(|ms| ms as f64 / 1000.0).collect()
+}
+
+/// The times of the `HMMT` KLVs among `klvs` and their descendants.
+fn hmmt_klvs(klvs: &[Klv]) -> Vec<u32> {
+    klvs.iter()
+        .flat_map(|klv| match (klv.header().fourcc().as_bytes(), klv.value()) {
+            (b"HMMT", Value::U32(times)) => times.iter().copied().filter(|&ms| ms != 0).collect(),
+            (_, Value::Nested(children)) => hmmt_klvs(children),
+            _ => Vec::new(),
+        })
+        .collect()
+}
+
+impl GpmfTrack {
+    /// HiLight times in _s_ from the start of the video, in order. Empty if
+    /// the track was loaded with [`GpmfTrack::from_mp4_reader`], which can't
+    /// see them; use [`GpmfTrack::with_hilights`] to attach them then.
+    pub fn hilights(&self) -> &[f64] {
+        &self.hilights
+    }
+
+    pub fn with_hilights(mut self, hilights: Vec<f64>) -> Self {
+        self.hilights = hilights;
+        self
+    }
+
+    /// [`GpmfTrack::hilights`] with the GPS fix of the payload each falls in.
+    pub fn hilights_with_gps9(&self) -> Vec<Hilight> {
+        self.hilights
+            .iter()
+            .map(|&time| {
+                let gps9 = self
+                    .find_nearest_sample((time * 1000.0) as u64)
+                    .map(|info| *info.gpmf_sample().gps9())
+                    .filter(|gps9| gps9.fix >= 2);
+                Hilight { time, gps9 }
+            })
+            .collect()
+    }
+
+    /// Writes [`GpmfTrack::hilights_with_gps9`] as GPX waypoints named
+    /// `HiLight 1`, `HiLight 2`, ... HiLights without a GPS fix are skipped.
+    #[cfg(feature = "gpx")]
+    pub fn write_hilights_gpx<W: std::io::Write>(
+        &self,
+        writer: &mut W,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        use gpx::{Gpx, GpxVersion, Waypoint};
+        use geo_types::Point;
+
+        let mut waypoints: Vec<Waypoint> = Vec::new();
+        for (index, hilight) in self.hilights_with_gps9().iter().enumerate() {
+            let Some(gps9) = hilight.gps9 else {
+                continue;
+            };
+
+            let mut waypoint = Waypoint::new(Point::new(gps9.longitude as f64, gps9.latitude as f64));
+            waypoint.elevation = Some(gps9.altitude as f64);
+            waypoint.time = gps9.to_datetime().map(gpx::Time::from);
+            waypoint.name = Some(format!("HiLight {}", index + 1));
+            waypoint.description = Some(format!("{:.3} s", hilight.time));
+            waypoints.push(waypoint);
+        }
+
+        let gpx = Gpx {
+            version: GpxVersion::Gpx11,
+            creator: Some("gpmf_tools".to_string()),
+            metadata: None,
+            waypoints,
+            tracks: vec![],
+            routes: vec![],
+        };
+
+        gpx::write(&gpx, writer)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use gpmf_parser::synth;
+
+    use crate::synth::GoproMp4Builder;
+
+    #[test]
+    fn hilights_are_read_from_box_and_gpmf() {
+        let udta = synth::nested(b"HLMT", &[synth::u32s(b"HMMT", &[2500, 0])]);
+        let mp4 = GoproMp4Builder::typical(3).hilights(&[500, 2500]).udta(udta).build();
+
+        let track = GpmfTrack::from_mp4_stream(&mut std::io::Cursor::new(mp4)).unwrap();
+
+        assert_eq!(track.hilights(), [0.5, 2.5]);
+        let hilights = track.hilights_with_gps9();
+        let latitude = |hilight: &Hilight| hilight.gps9.unwrap().latitude;
+        // Sample 0 starts at 35.0, sample 2 at 35.0 + 20e-5.
+        assert!((latitude(&hilights[0]) - 35.0).abs() < 1e-5);
+        assert!((latitude(&hilights[1]) - 35.0002).abs() < 1e-5);
+    }
+
+    #[cfg(feature = "gpx")]
+    #[test]
+    fn hilights_are_written_as_waypoints() {
+        let mp4 = GoproMp4Builder::typical(2).hilights(&[1200]).build();
+        let track = GpmfTrack::from_mp4_stream(&mut std::io::Cursor::new(mp4)).unwrap();
+
+        let mut gpx = Vec::new();
+        track.write_hilights_gpx(&mut gpx).unwrap();
+
+        let gpx = String::from_utf8(gpx).unwrap();
+        assert_eq!(gpx.matches("<wpt").count(), 1);
+        assert!(gpx.contains("HiLight 1"));
+    }
+}
@@ -0,0 +1,134 @@
+//! GPMF embedded in GoPro JPEG photos.
+//!
+//! GoPro stills carry their telemetry (GPS, orientation, exposure) in APP6
+//! segments whose payload starts with `GoPro\0`. A payload larger than a
+//! segment (64 KiB) is split over consecutive segments.
+
+use crate::{GpmfSampleInfo, GpmfTrack};
+
+const SOI: u8 = 0xD8;
+const EOI: u8 = 0xD9;
+const SOS: u8 = 0xDA;
+const APP6: u8 = 0xE6;
+
+/// Identifies GoPro's APP6 segments.
+pub const APP6_SIGNATURE: &[u8] = b"GoPro\0";
+
+/// Whether `bytes` starts like a JPEG file.
+pub fn is_jpeg(bytes: &[u8]) -> bool {
+    bytes.starts_with(&[0xFF, SOI])
+}
+
+/// Concatenates the GPMF payloads of the GoPro APP6 segments of a JPEG file.
+/// `Ok(None)` if it has none.
+pub fn extract_gpmf(bytes: &[u8]) -> Result<Option<Vec<u8>>, String> {
+    if !is_jpeg(bytes) {
+        return Err("Not a JPEG file".to_string());
+    }
+
+    let mut gpmf: Option<Vec<u8>> = None;
+    let mut position = 2;
+    loop {
+        // Markers may be preceded by any number of 0xFF fill bytes.
+        while bytes.get(position) == Some(&0xFF) && bytes.get(position + 1) == Some(&0xFF) {
+            position += 1;
+        }
+        let (Some(&0xFF), Some(&marker)) = (bytes.get(position), bytes.get(position + 1)) else {
+            return Err(format!("Expected a JPEG marker at offset {}", position));
+        };
+        position += 2;
+
+        match marker {
+            // The compressed image data follows; metadata segments precede it.
+            SOS | EOI => break,
+            // Markers without a payload.
+            0x01 | 0xD0..=0xD7 => continue,
+            _ => {}
+        }
+
+        let length = bytes
+            .get(position..position + 2)
+            .map(|length| u16::from_be_bytes([length[0], length[1]]) as usize)
+            .ok_or_else(|| format!("Truncated JPEG segment at offset {}", position))?;
+        let payload = length
+            .checked_sub(2)
+            .and_then(|payload_length| bytes.get(position + 2..position + 2 + payload_length))
+            .ok_or_else(|| format!("Truncated JPEG segment at offset {}", position))?;
+        position += length;
+
+        if marker == APP6
+            && let Some(segment_gpmf) = payload.strip_prefix(APP6_SIGNATURE)
+        {
+            gpmf.get_or_insert_with(Vec::new).extend_from_slice(segment_gpmf);
+        }
+    }
+
+    Ok(gpmf)
+}
+
+impl GpmfTrack {
+    /// Loads the GPMF of a GoPro JPEG photo as a track of a single sample,
+    /// without timing information.
+    pub fn from_jpeg_bytes(bytes: &[u8]) -> Result<Self, String> {
+        Self::from_jpeg_bytes_with(bytes, gpmf_parser::GpmfSampleParser::builtin())
+    }
+
+    /// [`GpmfTrack::from_jpeg_bytes`] decoding the sample with `parser`.
+    pub fn from_jpeg_bytes_with(bytes: &[u8], parser: &gpmf_parser::GpmfSampleParser) -> Result<Self, String> {
+        let gpmf = extract_gpmf(bytes)?.ok_or_else(|| "No GoPro APP6 segment found in the JPEG file".to_string())?;
+        let gpmf_sample_info = GpmfSampleInfo::from_bytes(&gpmf, None, parser)
+            .map_err(|e| format!("Failed to create GPMF sample info from APP6 payload: {}", e))?;
+
+        Ok(Self {
+            gpmf_sample_infos: vec![gpmf_sample_info],
+            camera_info: None,
+            hilights: Vec::new(),
+            mp4_tracks: Vec::new(),
+        })
+    }
+
+    pub fn from_jpeg_reader<R: std::io::Read>(reader: &mut R) -> Result<Self, String> {
+        Self::from_jpeg_reader_with(reader, gpmf_parser::GpmfSampleParser::builtin())
+    }
+
+    /// [`GpmfTrack::from_jpeg_reader`] decoding the sample with `parser`.
+    pub fn from_jpeg_reader_with<R: std::io::Read>(reader: &mut R, parser: &gpmf_parser::GpmfSampleParser) -> Result<Self, String> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)
+            .map_err(|e| format!("Failed to read JPEG file: {}", e))?;
+        Self::from_jpeg_bytes_with(&bytes, parser)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use gpmf_parser::synth::DevcBuilder;
+
+    use crate::synth;
+
+    #[test]
+    fn gpmf_split_over_segments_is_reassembled() {
+        let payload = DevcBuilder::typical(0).build();
+        let jpeg = synth::gopro_jpeg(&payload, 1000);
+
+        assert_eq!(extract_gpmf(&jpeg).unwrap().unwrap(), payload);
+
+        let track = GpmfTrack::from_jpeg_bytes(&jpeg).unwrap();
+        assert_eq!(track.gpmf_sample_infos().len(), 1);
+        let sample = track.gpmf_sample_infos()[0].gpmf_sample();
+        assert_eq!(sample.gps9().fix, 3);
+        assert_eq!(sample.accl().len(), 200);
+        assert!(track.gpmf_sample_infos()[0].time_range().is_none());
+    }
+
+    #[test]
+    fn jpeg_without_gpmf_is_rejected() {
+        let jpeg = [0xFF, SOI, 0xFF, 0xE0, 0, 4, 0, 0, 0xFF, EOI];
+
+        assert_eq!(extract_gpmf(&jpeg).unwrap(), None);
+        assert!(GpmfTrack::from_jpeg_bytes(&jpeg).is_err());
+        assert!(extract_gpmf(b"\0\0\0\x18ftypmp41").is_err());
+    }
+}
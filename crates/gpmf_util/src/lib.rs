@@ -1,13 +1,31 @@
 
+pub mod audio;
+pub mod camera_info;
+pub mod exposure;
+pub mod frame_skip;
+pub mod hilight;
+pub mod jpeg;
+mod mp4_index;
+pub mod raw;
+pub mod segment;
+pub mod summary;
+#[cfg(any(test, feature = "synth"))]
+pub mod synth;
+pub mod thermal;
+
 #[derive(Debug)]
 pub struct GpmfTrack {
     gpmf_sample_infos: Vec<GpmfSampleInfo>,
+    camera_info: Option<camera_info::CameraInfo>,
+    hilights: Vec<f64>,
+    /// Every track of the MP4 file the GPMF track was read from.
+    mp4_tracks: Vec<summary::Mp4TrackSummary>,
 }
 
 #[derive(Debug)]
 pub struct GpmfSampleInfo {
+    bytes: Vec<u8>,
     sample: gpmf_parser::GpmfSample,
-    #[cfg(feature = "mp4")]
     mp4_sample_info: Option<Mp4SampleInfo>,
 }
 
@@ -16,13 +34,38 @@ impl GpmfTrack {
         &self.gpmf_sample_infos
     }
 
+    /// Global metadata from the `udta` box. `None` if the file has none or
+    /// the track was loaded with [`GpmfTrack::from_mp4_reader`], which can't
+    /// see it; use [`GpmfTrack::with_camera_info`] to attach it then.
+    pub fn camera_info(&self) -> Option<&camera_info::CameraInfo> {
+        self.camera_info.as_ref()
+    }
+
+    pub fn with_camera_info(mut self, camera_info: Option<camera_info::CameraInfo>) -> Self {
+        self.camera_info = camera_info;
+        self
+    }
+
     #[cfg(feature = "mp4")]
     pub fn from_mp4_reader<R: std::io::Read + std::io::Seek>(
         mp4_reader: &mut mp4::Mp4Reader<R>,
         track_id: u32,
+    ) -> Result<Self, String> {
+        Self::from_mp4_reader_with(mp4_reader, track_id, gpmf_parser::GpmfSampleParser::builtin())
+    }
+
+    /// [`GpmfTrack::from_mp4_reader`] decoding the samples with `parser`.
+    #[cfg(feature = "mp4")]
+    pub fn from_mp4_reader_with<R: std::io::Read + std::io::Seek>(
+        mp4_reader: &mut mp4::Mp4Reader<R>,
+        track_id: u32,
+        parser: &gpmf_parser::GpmfSampleParser,
     ) -> Result<Self, String> {
         let sample_count = mp4_reader.sample_count(track_id)
             .map_err(|e| format!("Failed to get sample count for track {}: {}", track_id, e))?;
+        let timescale = mp4_reader.tracks().get(&track_id)
+            .ok_or_else(|| format!("Track {} does not exist", track_id))?
+            .timescale();
 
         let mut gpmf_sample_infos = Vec::with_capacity(sample_count as usize);
         for sample_idx in 0..sample_count {
@@ -32,29 +75,134 @@ impl GpmfTrack {
                 .map_err(|e| format!("Failed to read sample {} for track {}: {}", sample_id, track_id, e))?;
             let mp4_sample = mp4_sample.ok_or_else(|| format!("Sample {} for track {} does not exist", sample_id, track_id))?;
 
-            let gpmf_sample_info = GpmfSampleInfo::from_mp4_sample(&mp4_sample)
+            let gpmf_sample_info = GpmfSampleInfo::from_mp4_sample(&mp4_sample, timescale, parser)
                 .map_err(|e| format!("Failed to create GPMF sample info from MP4 sample {}: {}", sample_id, e))?;
             gpmf_sample_infos.push(gpmf_sample_info);
         }
 
-        Ok(Self { gpmf_sample_infos })
+        Ok(Self { gpmf_sample_infos, camera_info: None, hilights: Vec::new(), mp4_tracks: Vec::new() })
     }
 
-    #[cfg(feature = "mp4")]
+    /// Loads the GoPro GPMF track and the `udta` camera metadata from an MP4
+    /// stream, without the `mp4` crate.
+    ///
+    /// Only the `moov` box and the GPMF samples themselves are read; video and
+    /// audio data is skipped over.
+    pub fn from_mp4_stream<R: std::io::Read + std::io::Seek>(reader: &mut R) -> Result<Self, String> {
+        Self::from_mp4_stream_with(reader, gpmf_parser::GpmfSampleParser::builtin())
+    }
+
+    /// [`GpmfTrack::from_mp4_stream`] decoding the samples with `parser`, e.g.
+    /// one with additional [`gpmf_parser::StreamDecoder`]s registered.
+    pub fn from_mp4_stream_with<R: std::io::Read + std::io::Seek>(
+        reader: &mut R,
+        parser: &gpmf_parser::GpmfSampleParser,
+    ) -> Result<Self, String> {
+        let moov_index = mp4_index::blocking_io::read_moov_index(reader)
+            .map_err(|e| format!("Failed to read MP4 header: {}", e))?;
+        let track = moov_index.gpmf_track().ok_or(mp4_index::NO_GPMF_TRACK)?;
+
+        let mut gpmf_sample_infos = Vec::with_capacity(track.samples.len());
+        for (sample_idx, location) in track.samples.iter().enumerate() {
+            let sample_id = sample_idx + 1;
+
+            let bytes = mp4_index::blocking_io::read_sample(reader, location)
+                .map_err(|e| format!("Failed to read sample {} for track {}: {}", sample_id, track.track_id, e))?;
+
+            gpmf_sample_infos.push(GpmfSampleInfo::from_mp4_sample_bytes(&bytes, location, sample_id, parser)?);
+        }
+
+        Ok(Self::from_moov_index(&moov_index, gpmf_sample_infos))
+    }
+
+    /// Builds a track of the samples read from the GPMF track of `moov_index`,
+    /// with the camera metadata and HiLights of its `udta` box.
+    ///
+    /// Camera metadata is optional, so a `udta/GPMF` box that fails to parse
+    /// is logged and skipped instead of failing the telemetry load.
+    fn from_moov_index(moov_index: &mp4_index::MoovIndex, gpmf_sample_infos: Vec<GpmfSampleInfo>) -> Self {
+        let camera_info = moov_index.camera_info().unwrap_or_else(|e| {
+            log::warn!("Ignoring camera metadata: {}", e);
+            None
+        });
+        let hilights = hilight::hilights_of(&moov_index.hmmt, camera_info.as_ref());
+        let mp4_tracks = summary::mp4_tracks_of(moov_index);
+        Self { gpmf_sample_infos, camera_info, hilights, mp4_tracks }
+    }
+
+    /// Loads the GoPro GPMF track from an MP4 stream without requiring a
+    /// synchronous reader.
+    ///
+    /// Only the `moov` box and the GPMF samples themselves are read; video and
+    /// audio data is skipped over.
+    #[cfg(feature = "async")]
+    pub async fn from_async_mp4_reader<R>(reader: &mut R) -> Result<Self, String>
+    where
+        R: tokio::io::AsyncRead + tokio::io::AsyncSeek + Unpin,
+    {
+        Self::from_async_mp4_reader_with(reader, gpmf_parser::GpmfSampleParser::builtin()).await
+    }
+
+    /// [`GpmfTrack::from_async_mp4_reader`] decoding the samples with `parser`.
+    #[cfg(feature = "async")]
+    pub async fn from_async_mp4_reader_with<R>(reader: &mut R, parser: &gpmf_parser::GpmfSampleParser) -> Result<Self, String>
+    where
+        R: tokio::io::AsyncRead + tokio::io::AsyncSeek + Unpin,
+    {
+        let moov_index = mp4_index::async_io::read_moov_index(reader).await
+            .map_err(|e| format!("Failed to read MP4 header: {}", e))?;
+        let track = moov_index.gpmf_track().ok_or(mp4_index::NO_GPMF_TRACK)?;
+
+        let mut gpmf_sample_infos = Vec::with_capacity(track.samples.len());
+        for (sample_idx, location) in track.samples.iter().enumerate() {
+            let sample_id = sample_idx + 1;
+
+            let bytes = mp4_index::async_io::read_sample(reader, location).await
+                .map_err(|e| format!("Failed to read sample {} for track {}: {}", sample_id, track.track_id, e))?;
+
+            gpmf_sample_infos.push(GpmfSampleInfo::from_mp4_sample_bytes(&bytes, location, sample_id, parser)?);
+        }
+
+        Ok(Self::from_moov_index(&moov_index, gpmf_sample_infos))
+    }
+
+    /// Concatenates a per-sample series across the track, each value paired
+    /// with its time in _s_ (see [`GpmfSampleInfo::timestamps`]), e.g.
+    /// `track.timestamped_series(|sample| sample.shut())`.
+    ///
+    /// Samples that didn't come from an MP4 track are skipped.
+    pub fn timestamped_series<T: Copy>(
+        &self,
+        series: impl Fn(&gpmf_parser::GpmfSample) -> &[T],
+    ) -> Vec<(f64, T)> {
+        self.gpmf_sample_infos
+            .iter()
+            .filter_map(|info| info.timestamped(series(info.gpmf_sample())))
+            .flatten()
+            .collect()
+    }
+
+    /// How often each scene class was the most probable one across the track,
+    /// e.g. `track.scene_summary().fraction(SceneClass::Water)`.
+    pub fn scene_summary(&self) -> gpmf_parser::scene::SceneSummary {
+        let mut summary = gpmf_parser::scene::SceneSummary::default();
+        for info in &self.gpmf_sample_infos {
+            summary.add_sample(info.gpmf_sample());
+        }
+        summary
+    }
+
+    /// The last sample starting at or before `time_ms`.
+    ///
+    /// Returns `None` for samples that didn't come from an MP4 track, e.g. of
+    /// JPEG photos or raw GPMF dumps, as they have no timing.
     pub fn find_nearest_sample(&self, time_ms: u64) -> Option<&GpmfSampleInfo> {
+        let start_time_ms = |info: &GpmfSampleInfo| info.mp4_sample_info.as_ref().map(Mp4SampleInfo::start_time_ms);
         let idx = self
             .gpmf_sample_infos
-            .binary_search_by(|probe| probe.mp4_sample_info.as_ref().unwrap().start_time.cmp(&time_ms));
-        match idx {
-            Ok(idx) => Some(&self.gpmf_sample_infos[idx]),
-            Err(idx) => {
-                if idx == 0 {
-                    None
-                } else {
-                    Some(&self.gpmf_sample_infos[idx - 1])
-                }
-            }
-        }
+            .partition_point(|probe| start_time_ms(probe) <= Some(time_ms));
+        let nearest = self.gpmf_sample_infos[..idx].last()?;
+        nearest.mp4_sample_info.is_some().then_some(nearest)
     }
 
     #[cfg(feature = "gpx")]
@@ -112,49 +260,314 @@ impl GpmfTrack {
 }
 
 impl GpmfSampleInfo {
+    /// The sample payload as stored in the file.
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Timing of the MP4 sample the payload came from. `None` if it didn't
+    /// come from an MP4 track.
+    pub fn mp4_sample_info(&self) -> Option<&Mp4SampleInfo> {
+        self.mp4_sample_info.as_ref()
+    }
+
+    /// Top-level KLVs of the sample payload, i.e. one `DEVC` per device.
+    ///
+    /// Only the payload [`bytes`](GpmfSampleInfo::bytes) are kept, so they are
+    /// parsed again on every call.
+    pub fn klvs(&self) -> Vec<gpmf_parser::Klv> {
+        gpmf_parser::Klv::from_bytes(&self.bytes).expect("the payload was parsed when the sample was loaded")
+    }
+
     pub fn gpmf_sample(&self) -> &gpmf_parser::GpmfSample {
         &self.sample
     }
+
+    /// Start and end of the payload in _s_. `None` if it didn't come from an
+    /// MP4 track.
+    pub fn time_range(&self) -> Option<(f64, f64)> {
+        let info = self.mp4_sample_info.as_ref()?;
+        let timescale = info.timescale.max(1) as f64;
+        let start = info.start_time as f64 / timescale;
+        Some((start, start + info.duration as f64 / timescale))
+    }
+
+    /// Times in _s_ of `count` values spread evenly over the payload, such as
+    /// the one-per-video-frame values of [`gpmf_parser::GpmfSample::shut`].
+    pub fn timestamps(&self, count: usize) -> Option<Vec<f64>> {
+        let (start, end) = self.time_range()?;
+        let step = (end - start) / count.max(1) as f64;
+        Some((0..count).map(|i| start + i as f64 * step).collect())
+    }
+
+    /// Pairs each of `values` with its time from [`GpmfSampleInfo::timestamps`].
+    pub fn timestamped<T: Copy>(&self, values: &[T]) -> Option<Vec<(f64, T)>> {
+        let timestamps = self.timestamps(values.len())?;
+        Some(timestamps.into_iter().zip(values.iter().copied()).collect())
+    }
 }
 
-#[cfg(feature = "mp4")]
-#[derive(Debug)]
-struct Mp4SampleInfo {
-    start_time: u64,
-    duration: u32,
-    rendering_offset: i32,
-    is_sync: bool,
+/// Timing of an MP4 sample, as found in the sample tables of its track.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Mp4SampleInfo {
+    /// Decode time, in `timescale` units.
+    pub start_time: u64,
+    /// In `timescale` units.
+    pub duration: u32,
+    /// Units per second of `start_time` and `duration`.
+    pub timescale: u32,
+    /// Composition time offset from `ctts`, in `timescale` units.
+    pub rendering_offset: i32,
+    /// Whether the sample is a sync sample (`stss`). GPMF samples always are.
+    pub is_sync: bool,
+}
+
+impl Mp4SampleInfo {
+    fn start_time_ms(&self) -> u64 {
+        self.start_time * 1000 / self.timescale.max(1) as u64
+    }
 }
 
 impl GpmfSampleInfo {
-    fn from_mp4_sample(mp4_sample: &mp4::Mp4Sample) -> Result<Self, String> {
+    #[cfg(feature = "mp4")]
+    fn from_mp4_sample(mp4_sample: &mp4::Mp4Sample, timescale: u32, parser: &gpmf_parser::GpmfSampleParser) -> Result<Self, String> {
         let mp4_sample_info = Mp4SampleInfo {
             start_time: mp4_sample.start_time,
             duration: mp4_sample.duration,
+            timescale,
             rendering_offset: mp4_sample.rendering_offset,
             is_sync: mp4_sample.is_sync,
         };
 
-        Self::from_bytes(&mp4_sample.bytes, Some(mp4_sample_info))
+        Self::from_bytes(&mp4_sample.bytes, Some(mp4_sample_info), parser)
+    }
+
+    /// Parses the bytes of the `sample_id`th sample of a track indexed by
+    /// [`mp4_index`], located at `location`.
+    fn from_mp4_sample_bytes(
+        bytes: &[u8],
+        location: &mp4_index::SampleLocation,
+        sample_id: usize,
+        parser: &gpmf_parser::GpmfSampleParser,
+    ) -> Result<Self, String> {
+        Self::from_bytes(bytes, Some(location.info), parser)
+            .map_err(|e| format!("Failed to create GPMF sample info from MP4 sample {}: {}", sample_id, e))
     }
 
     fn from_bytes(
         bytes: &[u8],
         mp4_sample_info: Option<Mp4SampleInfo>,
+        parser: &gpmf_parser::GpmfSampleParser,
     ) -> Result<Self, String> {
-        let klvs = gpmf_parser::Klv::from_reader(&mut std::io::Cursor::new(bytes))
+        let mut klvs = gpmf_parser::Klv::from_bytes(bytes)
             .map_err(|e| format!("Failed to parse GPMF KLVs: {}", e))?;
 
-        let devc_klv = klvs
+        let devc_index = klvs
             .iter()
-            .find(|klv| klv.header().fourcc().as_str() == "DEVC")
+            .position(|klv| klv.header().fourcc().as_str() == "DEVC")
             .ok_or("DEVC KLV not found")?;
 
-        let sample = gpmf_parser::GpmfSample::new(devc_klv);
+        let sample = parser.parse_owned(klvs.swap_remove(devc_index));
 
         Ok(Self {
+            bytes: bytes.to_vec(),
             sample,
             mp4_sample_info,
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use gpmf_parser::synth::{self, DevcBuilder};
+    use gpmf_parser::GpmfSampleParser;
+
+    use crate::synth::GoproMp4Builder;
+
+    /// A `DEVC` payload with a single `GPS9` sample at the given latitude.
+    fn gps9_payload(latitude: f32) -> Vec<u8> {
+        DevcBuilder::new(1, "Camera").gps9(&[synth::gps9_fix(latitude, 139.0)]).build()
+    }
+
+    pub(crate) fn track_of(payloads: &[Vec<u8>]) -> GpmfTrack {
+        let gpmf_sample_infos = payloads
+            .iter()
+            .enumerate()
+            .map(|(i, payload)| {
+                let info = Mp4SampleInfo { start_time: i as u64 * 1001, duration: 1001, timescale: 1000, rendering_offset: 0, is_sync: true };
+                GpmfSampleInfo::from_bytes(payload, Some(info), GpmfSampleParser::builtin()).unwrap()
+            })
+            .collect();
+        GpmfTrack { gpmf_sample_infos, camera_info: None, hilights: Vec::new(), mp4_tracks: Vec::new() }
+    }
+
+    #[test]
+    fn from_bytes_decodes_devc_payload() {
+        let info = GpmfSampleInfo::from_bytes(&DevcBuilder::typical(0).build(), None, GpmfSampleParser::builtin()).unwrap();
+
+        assert_eq!(info.klvs().len(), 1);
+        assert_eq!(info.gpmf_sample().gps9().fix, 3);
+        assert_eq!(info.gpmf_sample().accl().len(), 200);
+    }
+
+    #[test]
+    fn from_bytes_requires_devc() {
+        let payload = synth::ascii(b"STNM", "not a DEVC");
+
+        assert!(GpmfSampleInfo::from_bytes(&payload, None, GpmfSampleParser::builtin()).is_err());
+    }
+
+    #[test]
+    fn find_nearest_sample_picks_preceding_sample() {
+        let track = track_of(&[gps9_payload(35.5), gps9_payload(35.6), gps9_payload(35.7)]);
+
+        assert!(track.find_nearest_sample(0).is_some());
+        let nearest = track.find_nearest_sample(2001).unwrap();
+        assert!((nearest.gpmf_sample().gps9().latitude - 35.6).abs() < 1e-4);
+    }
+
+    #[test]
+    fn find_nearest_sample_needs_timing() {
+        let track = GpmfTrack::from_raw_gpmf_bytes(&crate::synth::length_prefixed(&[gps9_payload(35.5), gps9_payload(35.6)])).unwrap();
+
+        assert!(track.find_nearest_sample(0).is_none());
+        assert!(track.find_nearest_sample(5000).is_none());
+    }
+
+    #[cfg(feature = "async")]
+    fn two_sample_mp4() -> Vec<u8> {
+        GoproMp4Builder::new()
+            .sample(gps9_payload(35.5), 1001)
+            .sample(gps9_payload(35.6), 1001)
+            .build()
+    }
+
+    #[test]
+    fn per_frame_series_are_timestamped() {
+        let payload = |exposure: f32| DevcBuilder::new(1, "Camera").shut(&[exposure; 4]).build();
+        let track = track_of(&[payload(0.01), payload(0.02)]);
+
+        let (start, end) = track.gpmf_sample_infos()[1].time_range().unwrap();
+        assert!((start - 1.001).abs() < 1e-9 && (end - 2.002).abs() < 1e-9);
+
+        let series = track.timestamped_series(|sample| sample.shut());
+        assert_eq!(series.len(), 8);
+        assert!((series[5].0 - (1.001 + 1.001 / 4.0)).abs() < 1e-9);
+        assert!((series[5].1 - 0.02).abs() < 1e-6);
+        assert!(series.windows(2).all(|w| w[0].0 < w[1].0));
+    }
+
+    #[test]
+    fn scene_summary_covers_whole_track() {
+        use gpmf_parser::scene::{SceneClass, SceneProbability};
+
+        let frame = |class| vec![SceneProbability { class, probability: 0.8 }];
+        let payload = |class| DevcBuilder::new(1, "Camera").scen(&[frame(class), frame(class)]).build();
+        let track = track_of(&[payload(SceneClass::Water), payload(SceneClass::Water), payload(SceneClass::Snow)]);
+
+        let summary = track.scene_summary();
+        assert_eq!(summary.frame_count(), 6);
+        assert!((summary.fraction(SceneClass::Water) - 2.0 / 3.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn from_mp4_stream_loads_samples_and_camera_info() {
+        let udta = [synth::ascii(b"FMWR", "H22.01.01.10.70"), synth::ascii(b"MINF", "HERO11 Black")].concat();
+        let mp4 = GoproMp4Builder::typical(2).udta(udta).build();
+
+        let track = GpmfTrack::from_mp4_stream(&mut std::io::Cursor::new(mp4)).unwrap();
+
+        assert_eq!(track.gpmf_sample_infos().len(), 2);
+        assert_eq!(track.gpmf_sample_infos()[1].gpmf_sample().accl().len(), 200);
+        assert_eq!(track.gpmf_sample_infos()[1].bytes(), DevcBuilder::typical(1).build());
+        let mp4_sample_info = track.gpmf_sample_infos()[1].mp4_sample_info().unwrap();
+        assert_eq!((mp4_sample_info.start_time, mp4_sample_info.duration, mp4_sample_info.timescale), (1001, 1001, 1000));
+        assert!(mp4_sample_info.is_sync);
+        let camera_info = track.camera_info().unwrap();
+        assert_eq!(camera_info.firmware.as_deref(), Some("H22.01.01.10.70"));
+        assert_eq!(camera_info.model.as_deref(), Some("HERO11 Black"));
+
+        let mp4 = GoproMp4Builder::typical(1).build();
+        let track = GpmfTrack::from_mp4_stream(&mut std::io::Cursor::new(mp4)).unwrap();
+        assert!(track.camera_info().is_none());
+    }
+
+    #[test]
+    fn from_mp4_stream_with_uses_the_given_parser() {
+        use gpmf_parser::klv::Fourcc;
+        use gpmf_parser::{DecodedStream, StreamContext};
+
+        let mut parser = GpmfSampleParser::empty();
+        parser.register(Fourcc(*b"ACCL"), |stream: &StreamContext<'_>| {
+            Some(DecodedStream::new(stream.data().header().tsr().repeat()))
+        });
+        let mp4 = GoproMp4Builder::typical(2).build();
+
+        let track = GpmfTrack::from_mp4_stream_with(&mut std::io::Cursor::new(mp4), &parser).unwrap();
+
+        let sample = track.gpmf_sample_infos()[1].gpmf_sample();
+        assert_eq!(sample.decoded_as::<u16>(Fourcc(*b"ACCL")), Some(&200));
+        assert!(sample.accl().is_empty(), "ACCL decoder isn't registered on an empty parser");
+    }
+
+    #[test]
+    fn malformed_camera_info_does_not_fail_the_track() {
+        let udta = synth::klv(b"FMWR", b'x', 1, 1, b"?");
+        let mp4 = GoproMp4Builder::typical(2).udta(udta).hilights(&[500]).build();
+
+        let track = GpmfTrack::from_mp4_stream(&mut std::io::Cursor::new(mp4)).unwrap();
+
+        assert_eq!(track.gpmf_sample_infos().len(), 2);
+        assert!(track.camera_info().is_none());
+        assert_eq!(track.hilights(), [0.5]);
+    }
+
+    #[cfg(feature = "mp4")]
+    #[test]
+    fn from_mp4_reader_loads_gpmf_samples() {
+        let mp4 = GoproMp4Builder::new()
+            .timescale(90_000)
+            .sample(gps9_payload(35.5), 90_000)
+            .sample(gps9_payload(35.6), 45_000)
+            .sample(gps9_payload(35.7), 90_000)
+            .build();
+        let size = mp4.len() as u64;
+
+        let mut mp4_reader = mp4::Mp4Reader::read_header(std::io::Cursor::new(mp4), size).unwrap();
+        let track_id = *mp4_reader.tracks().keys().next().unwrap();
+        let track = GpmfTrack::from_mp4_reader(&mut mp4_reader, track_id).unwrap();
+
+        let sample_infos = track.gpmf_sample_infos();
+        assert_eq!(sample_infos.len(), 3);
+        assert!((sample_infos[2].gpmf_sample().gps9().latitude - 35.7).abs() < 1e-4);
+        assert_eq!(sample_infos[2].mp4_sample_info.unwrap().start_time, 135_000);
+        assert_eq!(sample_infos[2].time_range(), Some((1.5, 2.5)));
+        assert!(std::ptr::eq(track.find_nearest_sample(1600).unwrap(), &sample_infos[2]));
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn from_async_mp4_reader_loads_gpmf_samples() {
+        let mp4 = two_sample_mp4();
+
+        let track = GpmfTrack::from_async_mp4_reader(&mut std::io::Cursor::new(mp4)).await.unwrap();
+
+        let sample_infos = track.gpmf_sample_infos();
+        assert_eq!(sample_infos.len(), 2);
+        assert!((sample_infos[1].gpmf_sample().gps9().latitude - 35.6).abs() < 1e-4);
+        assert_eq!(sample_infos[1].mp4_sample_info.unwrap().start_time, 1001);
+        assert!(std::ptr::eq(track.find_nearest_sample(1500).unwrap(), &sample_infos[1]));
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn from_async_mp4_reader_requires_gpmf_track() {
+        let mut mp4 = two_sample_mp4();
+        mp4.truncate(20); // `ftyp` only.
+
+        let result = GpmfTrack::from_async_mp4_reader(&mut std::io::Cursor::new(mp4)).await;
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,637 @@
+//! Minimal MP4 sample-table reader, independent of the `mp4` crate.
+//!
+//! Only the boxes needed to locate and time the samples of a track are
+//! understood. Everything else is skipped.
+
+use crate::camera_info::CameraInfo;
+use crate::Mp4SampleInfo;
+
+pub(crate) const NO_GPMF_TRACK: &str = "No GPMF track found in the MP4 stream";
+
+/// Box header: type and the size of the payload following the header.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct BoxHeader {
+    pub box_type: [u8; 4],
+    pub header_size: u64,
+    /// `None` if the box extends to the end of the file.
+    pub payload_size: Option<u64>,
+}
+
+impl BoxHeader {
+    /// Size of the largest header [`BoxHeader::parse`] may need.
+    pub const MAX_SIZE: usize = 16;
+
+    /// Parses a box header from the start of `bytes`. `bytes` needs to hold
+    /// 16 bytes only if the box uses a 64-bit size.
+    pub fn parse(bytes: &[u8]) -> Result<Self, String> {
+        if bytes.len() < 8 {
+            return Err("Truncated MP4 box header".to_string());
+        }
+        let size = u32::from_be_bytes(bytes[0..4].try_into().unwrap()) as u64;
+        let box_type: [u8; 4] = bytes[4..8].try_into().unwrap();
+
+        let (header_size, total_size) = match size {
+            0 => (8, None),
+            1 => {
+                if bytes.len() < 16 {
+                    return Err("Truncated MP4 box header".to_string());
+                }
+                (16, Some(u64::from_be_bytes(bytes[8..16].try_into().unwrap())))
+            }
+            _ => (8, Some(size)),
+        };
+
+        let payload_size = match total_size {
+            Some(total_size) if total_size < header_size => {
+                return Err(format!("Invalid size {} for MP4 box '{}'", total_size, fourcc_str(&box_type)));
+            }
+            Some(total_size) => Some(total_size - header_size),
+            None => None,
+        };
+
+        Ok(Self { box_type, header_size, payload_size })
+    }
+}
+
+/// Iterates over the child boxes contained in `bytes`.
+pub(crate) fn child_boxes(bytes: &[u8]) -> impl Iterator<Item = Result<([u8; 4], &[u8]), String>> {
+    let mut rest = bytes;
+    std::iter::from_fn(move || {
+        if rest.is_empty() {
+            return None;
+        }
+        let result = BoxHeader::parse(rest).and_then(|header| {
+            let start = header.header_size as usize;
+            let end = match header.payload_size {
+                Some(payload_size) => start + payload_size as usize,
+                None => rest.len(),
+            };
+            if end > rest.len() {
+                return Err(format!("MP4 box '{}' exceeds its parent", fourcc_str(&header.box_type)));
+            }
+            let payload = &rest[start..end];
+            rest = &rest[end..];
+            Ok((header.box_type, payload))
+        });
+        if result.is_err() {
+            rest = &[];
+        }
+        Some(result)
+    })
+}
+
+fn find_child<'a>(bytes: &'a [u8], box_type: &[u8; 4]) -> Result<Option<&'a [u8]>, String> {
+    for child in child_boxes(bytes) {
+        let (child_type, payload) = child?;
+        if &child_type == box_type {
+            return Ok(Some(payload));
+        }
+    }
+    Ok(None)
+}
+
+fn fourcc_str(fourcc: &[u8; 4]) -> String {
+    String::from_utf8_lossy(fourcc).into_owned()
+}
+
+/// Big-endian reader over a full box payload.
+struct BoxReader<'a> {
+    bytes: &'a [u8],
+    box_type: &'static str,
+}
+
+impl<'a> BoxReader<'a> {
+    fn new(bytes: &'a [u8], box_type: &'static str) -> Self {
+        Self { bytes, box_type }
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+        if self.bytes.len() < len {
+            return Err(format!("Truncated '{}' box", self.box_type));
+        }
+        let (head, tail) = self.bytes.split_at(len);
+        self.bytes = tail;
+        Ok(head)
+    }
+
+    fn u8(&mut self) -> Result<u8, String> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32, String> {
+        Ok(u32::from_be_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> Result<u64, String> {
+        Ok(u64::from_be_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    /// Reads the entry count of a table, checking that the rest of the box
+    /// holds that many entries of `entry_size` bytes before anything is
+    /// allocated for them.
+    fn entry_count(&mut self, entry_size: usize) -> Result<usize, String> {
+        let count = self.u32()? as usize;
+        if count.checked_mul(entry_size).is_none_or(|size| size > self.bytes.len()) {
+            return Err(format!(
+                "'{}' box claims {} entries but only has {} bytes left",
+                self.box_type,
+                count,
+                self.bytes.len()
+            ));
+        }
+        Ok(count)
+    }
+
+    /// Reads the version byte and skips the flags of a full box.
+    fn version_and_flags(&mut self) -> Result<u8, String> {
+        let version = self.u8()?;
+        self.take(3)?;
+        Ok(version)
+    }
+
+    fn rest(&mut self) -> &'a [u8] {
+        std::mem::take(&mut self.bytes)
+    }
+}
+
+/// A track described by a `trak` box, with its sample table expanded.
+#[derive(Debug)]
+pub(crate) struct TrackIndex {
+    pub track_id: u32,
+    pub handler_type: [u8; 4],
+    pub handler_name: String,
+    pub samples: Vec<SampleLocation>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct SampleLocation {
+    pub offset: u64,
+    pub size: u32,
+    pub info: Mp4SampleInfo,
+}
+
+impl TrackIndex {
+    /// Whether this is GoPro's timed-metadata track carrying GPMF.
+    pub fn is_gpmf(&self) -> bool {
+        &self.handler_type == b"meta" && self.handler_name.contains("GoPro MET")
+    }
+}
+
+/// The tracks described by a `moov` box.
+#[derive(Debug)]
+pub(crate) struct MoovIndex {
+    pub tracks: Vec<TrackIndex>,
+    /// Payload of the `udta/GPMF` box holding GoPro's global metadata.
+    pub udta_gpmf: Option<Vec<u8>>,
+    /// HiLight times in _ms_ from the `udta/HMMT` box.
+    pub hmmt: Vec<u32>,
+}
+
+impl MoovIndex {
+    /// Parses the payload of a `moov` box of a file of `file_size` bytes.
+    pub fn parse(moov: &[u8], file_size: u64) -> Result<Self, String> {
+        let mut tracks = Vec::new();
+        let mut udta_gpmf = None;
+        let mut hmmt = Vec::new();
+        for child in child_boxes(moov) {
+            let (box_type, payload) = child?;
+            match &box_type {
+                b"trak" => tracks.push(parse_trak(payload, file_size)?),
+                b"udta" => {
+                    udta_gpmf = find_child(payload, b"GPMF")?.map(<[u8]>::to_vec);
+                    if let Some(hmmt_box) = find_child(payload, b"HMMT")? {
+                        hmmt = parse_hmmt(hmmt_box)?;
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(Self { tracks, udta_gpmf, hmmt })
+    }
+
+    pub fn gpmf_track(&self) -> Option<&TrackIndex> {
+        self.tracks.iter().find(|track| track.is_gpmf())
+    }
+
+    pub fn camera_info(&self) -> Result<Option<CameraInfo>, String> {
+        self.udta_gpmf.as_deref().map(CameraInfo::from_bytes).transpose()
+    }
+}
+
+/// `HMMT` is a plain box: a count followed by that many times in _ms_.
+fn parse_hmmt(hmmt: &[u8]) -> Result<Vec<u32>, String> {
+    let mut reader = BoxReader::new(hmmt, "HMMT");
+    let count = reader.entry_count(4)?;
+    (0..count).map(|_| reader.u32()).collect()
+}
+
+fn parse_trak(trak: &[u8], file_size: u64) -> Result<TrackIndex, String> {
+    let tkhd = find_child(trak, b"tkhd")?.ok_or("'trak' box without 'tkhd'")?;
+    let track_id = {
+        let mut reader = BoxReader::new(tkhd, "tkhd");
+        let version = reader.version_and_flags()?;
+        // creation_time, modification_time
+        reader.take(if version == 1 { 16 } else { 8 })?;
+        reader.u32()?
+    };
+
+    let mdia = find_child(trak, b"mdia")?.ok_or("'trak' box without 'mdia'")?;
+
+    let mdhd = find_child(mdia, b"mdhd")?.ok_or("'mdia' box without 'mdhd'")?;
+    let timescale = {
+        let mut reader = BoxReader::new(mdhd, "mdhd");
+        let version = reader.version_and_flags()?;
+        // creation_time, modification_time
+        reader.take(if version == 1 { 16 } else { 8 })?;
+        reader.u32()?
+    };
+
+    let hdlr = find_child(mdia, b"hdlr")?.ok_or("'mdia' box without 'hdlr'")?;
+    let (handler_type, handler_name) = {
+        let mut reader = BoxReader::new(hdlr, "hdlr");
+        reader.version_and_flags()?;
+        reader.take(4)?; // pre_defined
+        let handler_type: [u8; 4] = reader.take(4)?.try_into().unwrap();
+        reader.take(12)?; // reserved
+        let name = String::from_utf8_lossy(reader.rest())
+            .trim_end_matches('\0')
+            .to_string();
+        (handler_type, name)
+    };
+
+    let stbl = find_child(mdia, b"minf")?
+        .map(|minf| find_child(minf, b"stbl"))
+        .transpose()?
+        .flatten()
+        .ok_or("'mdia' box without 'minf/stbl'")?;
+    let samples = parse_stbl(stbl, timescale, file_size)?;
+
+    Ok(TrackIndex {
+        track_id,
+        handler_type,
+        handler_name,
+        samples,
+    })
+}
+
+/// Expands the sample table of a track. Counts are checked against the box
+/// sizes and `file_size` before allocating, so hostile files fail instead of
+/// exhausting memory.
+fn parse_stbl(stbl: &[u8], timescale: u32, file_size: u64) -> Result<Vec<SampleLocation>, String> {
+    let mut sizes: Vec<u32> = Vec::new();
+    let mut chunk_offsets: Vec<u64> = Vec::new();
+    // (first_chunk, samples_per_chunk)
+    let mut sample_to_chunk: Vec<(u32, u32)> = Vec::new();
+    // (sample_count, sample_delta)
+    let mut time_to_sample: Vec<(u32, u32)> = Vec::new();
+    // (sample_count, sample_offset)
+    let mut composition_offsets: Vec<(u32, i32)> = Vec::new();
+    let mut sync_samples: Option<Vec<u32>> = None;
+
+    for child in child_boxes(stbl) {
+        let (box_type, payload) = child?;
+        match &box_type {
+            b"stsz" => {
+                let mut reader = BoxReader::new(payload, "stsz");
+                reader.version_and_flags()?;
+                let sample_size = reader.u32()?;
+                sizes = if sample_size != 0 {
+                    let sample_count = reader.u32()?;
+                    if sample_count as u64 * sample_size as u64 > file_size {
+                        return Err(format!(
+                            "'stsz' box claims {} samples of {} bytes, more than the file holds",
+                            sample_count, sample_size
+                        ));
+                    }
+                    vec![sample_size; sample_count as usize]
+                } else {
+                    let sample_count = reader.entry_count(4)?;
+                    (0..sample_count).map(|_| reader.u32()).collect::<Result<_, _>>()?
+                };
+            }
+            b"stco" => {
+                let mut reader = BoxReader::new(payload, "stco");
+                reader.version_and_flags()?;
+                let entry_count = reader.entry_count(4)?;
+                chunk_offsets = (0..entry_count)
+                    .map(|_| reader.u32().map(u64::from))
+                    .collect::<Result<_, _>>()?;
+            }
+            b"co64" => {
+                let mut reader = BoxReader::new(payload, "co64");
+                reader.version_and_flags()?;
+                let entry_count = reader.entry_count(8)?;
+                chunk_offsets = (0..entry_count).map(|_| reader.u64()).collect::<Result<_, _>>()?;
+            }
+            b"stsc" => {
+                let mut reader = BoxReader::new(payload, "stsc");
+                reader.version_and_flags()?;
+                let entry_count = reader.entry_count(12)?;
+                for _ in 0..entry_count {
+                    let first_chunk = reader.u32()?;
+                    let samples_per_chunk = reader.u32()?;
+                    reader.u32()?; // sample_description_index
+                    sample_to_chunk.push((first_chunk, samples_per_chunk));
+                }
+            }
+            b"stts" => {
+                let mut reader = BoxReader::new(payload, "stts");
+                reader.version_and_flags()?;
+                let entry_count = reader.entry_count(8)?;
+                for _ in 0..entry_count {
+                    time_to_sample.push((reader.u32()?, reader.u32()?));
+                }
+            }
+            b"ctts" => {
+                let mut reader = BoxReader::new(payload, "ctts");
+                reader.version_and_flags()?;
+                let entry_count = reader.entry_count(8)?;
+                for _ in 0..entry_count {
+                    composition_offsets.push((reader.u32()?, reader.u32()? as i32));
+                }
+            }
+            b"stss" => {
+                let mut reader = BoxReader::new(payload, "stss");
+                reader.version_and_flags()?;
+                let entry_count = reader.entry_count(4)?;
+                sync_samples = Some((0..entry_count).map(|_| reader.u32()).collect::<Result<_, _>>()?);
+            }
+            _ => {}
+        }
+    }
+
+    // Expand the chunk tables into per-sample file offsets.
+    let mut offsets: Vec<u64> = Vec::with_capacity(sizes.len());
+    for (entry_idx, &(first_chunk, samples_per_chunk)) in sample_to_chunk.iter().enumerate() {
+        let last_chunk = sample_to_chunk
+            .get(entry_idx + 1)
+            .map(|&(next_first_chunk, _)| next_first_chunk)
+            .unwrap_or(chunk_offsets.len() as u32 + 1);
+        for chunk in first_chunk..last_chunk {
+            let mut offset = *(chunk as usize)
+                .checked_sub(1)
+                .and_then(|chunk_idx| chunk_offsets.get(chunk_idx))
+                .ok_or("'stsc' refers to a chunk missing from 'stco'")?;
+            for _ in 0..samples_per_chunk {
+                let Some(&size) = sizes.get(offsets.len()) else { break };
+                let end = offset
+                    .checked_add(size as u64)
+                    .filter(|&end| end <= file_size)
+                    .ok_or_else(|| format!("Sample {} exceeds the file", offsets.len() + 1))?;
+                offsets.push(offset);
+                offset = end;
+            }
+        }
+    }
+    if offsets.len() != sizes.len() {
+        return Err(format!(
+            "Sample table locates {} of {} samples",
+            offsets.len(),
+            sizes.len()
+        ));
+    }
+
+    // Lazily expanded, and never further than the sample count.
+    let mut durations = time_to_sample
+        .iter()
+        .flat_map(|&(count, delta)| std::iter::repeat_n(delta, count as usize))
+        .take(sizes.len());
+    let mut rendering_offsets = composition_offsets
+        .iter()
+        .flat_map(|&(count, offset)| std::iter::repeat_n(offset, count as usize))
+        .take(sizes.len());
+
+    let mut samples = Vec::with_capacity(sizes.len());
+    let mut start_time: u64 = 0;
+    for (sample_idx, (&offset, &size)) in offsets.iter().zip(sizes.iter()).enumerate() {
+        let sample_id = sample_idx as u32 + 1;
+        let duration = durations.next().unwrap_or(0);
+        let is_sync = sync_samples
+            .as_ref()
+            .is_none_or(|sync_samples| sync_samples.binary_search(&sample_id).is_ok());
+
+        samples.push(SampleLocation {
+            offset,
+            size,
+            info: Mp4SampleInfo {
+                start_time,
+                duration,
+                timescale,
+                rendering_offset: rendering_offsets.next().unwrap_or(0),
+                is_sync,
+            },
+        });
+        start_time += duration as u64;
+    }
+
+    Ok(samples)
+}
+
+/// The walk over the top-level boxes of an MP4 stream looking for `moov`,
+/// independent of how the bytes are read, so the blocking and async readers
+/// share it.
+pub(crate) struct MoovLocator {
+    file_size: u64,
+    position: u64,
+}
+
+impl MoovLocator {
+    pub const NOT_FOUND: &'static str = "No 'moov' box found in the MP4 stream";
+
+    pub fn new(file_size: u64) -> Self {
+        Self { file_size, position: 0 }
+    }
+
+    /// Offset and length of the next box header to read, or `None` once the
+    /// whole stream has been walked.
+    pub fn next_header(&self) -> Option<(u64, usize)> {
+        (self.position < self.file_size).then(|| {
+            let available = (self.file_size - self.position).min(BoxHeader::MAX_SIZE as u64) as usize;
+            (self.position, available)
+        })
+    }
+
+    /// Moves past the box whose header was read at [`Self::next_header`].
+    /// Returns the offset and size of the `moov` payload if this is it.
+    pub fn advance(&mut self, header_bytes: &[u8]) -> Result<Option<(u64, usize)>, String> {
+        let header = BoxHeader::parse(header_bytes)?;
+
+        let remaining = (self.file_size - self.position).saturating_sub(header.header_size);
+        let payload_size = header.payload_size.unwrap_or(remaining);
+        if payload_size > remaining {
+            return Err(format!(
+                "MP4 box '{}' at offset {} exceeds the file",
+                fourcc_str(&header.box_type),
+                self.position
+            ));
+        }
+
+        let payload_position = self.position + header.header_size;
+        self.position = payload_position + payload_size;
+        Ok((&header.box_type == b"moov").then_some((payload_position, payload_size as usize)))
+    }
+
+    pub fn parse_moov(&self, moov: &[u8]) -> Result<MoovIndex, String> {
+        MoovIndex::parse(moov, self.file_size)
+    }
+}
+
+pub(crate) mod blocking_io {
+    use std::io::{Read, Seek, SeekFrom};
+
+    use super::{MoovIndex, MoovLocator, SampleLocation};
+
+    /// Walks the top-level boxes of an MP4 stream and parses its `moov` box,
+    /// without reading any media data.
+    pub fn read_moov_index<R: Read + Seek>(reader: &mut R) -> Result<MoovIndex, String> {
+        let file_size = reader.seek(SeekFrom::End(0))
+            .map_err(|e| format!("Failed to seek MP4 stream: {}", e))?;
+
+        let mut locator = MoovLocator::new(file_size);
+        while let Some((position, len)) = locator.next_header() {
+            let header_bytes = read_at(reader, position, len, "MP4 box header")?;
+            if let Some((position, len)) = locator.advance(&header_bytes)? {
+                let moov = read_at(reader, position, len, "'moov' box")?;
+                return locator.parse_moov(&moov);
+            }
+        }
+
+        Err(MoovLocator::NOT_FOUND.to_string())
+    }
+
+    pub fn read_sample<R: Read + Seek>(reader: &mut R, location: &SampleLocation) -> Result<Vec<u8>, String> {
+        read_at(reader, location.offset, location.size as usize, "sample")
+    }
+
+    fn read_at<R: Read + Seek>(reader: &mut R, position: u64, len: usize, what: &str) -> Result<Vec<u8>, String> {
+        reader.seek(SeekFrom::Start(position))
+            .map_err(|e| format!("Failed to seek to {} at offset {}: {}", what, position, e))?;
+        let mut bytes = vec![0u8; len];
+        reader.read_exact(&mut bytes)
+            .map_err(|e| format!("Failed to read {} at offset {}: {}", what, position, e))?;
+        Ok(bytes)
+    }
+}
+
+#[cfg(feature = "async")]
+pub(crate) mod async_io {
+    use tokio::io::{AsyncRead, AsyncReadExt as _, AsyncSeek, AsyncSeekExt as _};
+
+    use std::io::SeekFrom;
+
+    use super::{MoovIndex, MoovLocator, SampleLocation};
+
+    /// Asynchronous counterpart of [`super::blocking_io::read_moov_index`].
+    pub async fn read_moov_index<R: AsyncRead + AsyncSeek + Unpin>(reader: &mut R) -> Result<MoovIndex, String> {
+        let file_size = reader.seek(SeekFrom::End(0)).await
+            .map_err(|e| format!("Failed to seek MP4 stream: {}", e))?;
+
+        let mut locator = MoovLocator::new(file_size);
+        while let Some((position, len)) = locator.next_header() {
+            let header_bytes = read_at(reader, position, len, "MP4 box header").await?;
+            if let Some((position, len)) = locator.advance(&header_bytes)? {
+                let moov = read_at(reader, position, len, "'moov' box").await?;
+                return locator.parse_moov(&moov);
+            }
+        }
+
+        Err(MoovLocator::NOT_FOUND.to_string())
+    }
+
+    pub async fn read_sample<R: AsyncRead + AsyncSeek + Unpin>(
+        reader: &mut R,
+        location: &SampleLocation,
+    ) -> Result<Vec<u8>, String> {
+        read_at(reader, location.offset, location.size as usize, "sample").await
+    }
+
+    async fn read_at<R: AsyncRead + AsyncSeek + Unpin>(
+        reader: &mut R,
+        position: u64,
+        len: usize,
+        what: &str,
+    ) -> Result<Vec<u8>, String> {
+        reader.seek(SeekFrom::Start(position)).await
+            .map_err(|e| format!("Failed to seek to {} at offset {}: {}", what, position, e))?;
+        let mut bytes = vec![0u8; len];
+        reader.read_exact(&mut bytes).await
+            .map_err(|e| format!("Failed to read {} at offset {}: {}", what, position, e))?;
+        Ok(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mp4_box(box_type: &[u8; 4], payload: &[u8]) -> Vec<u8> {
+        [&((payload.len() + 8) as u32).to_be_bytes()[..], box_type, payload].concat()
+    }
+
+    /// A version 0 full box whose fields are `u32`s.
+    fn table_box(box_type: &[u8; 4], fields: &[u32]) -> Vec<u8> {
+        let fields: Vec<u8> = fields.iter().flat_map(|v| v.to_be_bytes()).collect();
+        mp4_box(box_type, &[&[0; 4][..], &fields].concat())
+    }
+
+    /// The payload of an `stbl` box locating 2 samples of 100 bytes at
+    /// offset 8, with `overrides` replacing the boxes of the same type.
+    fn stbl(overrides: &[Vec<u8>]) -> Vec<u8> {
+        let boxes = [
+            table_box(b"stts", &[1, 2, 1001]),
+            table_box(b"stsc", &[1, 1, 2, 1]),
+            table_box(b"stsz", &[0, 2, 100, 100]),
+            table_box(b"stco", &[1, 8]),
+        ];
+        boxes
+            .iter()
+            .flat_map(|default| overrides.iter().find(|b| b[4..8] == default[4..8]).unwrap_or(default))
+            .copied()
+            .collect()
+    }
+
+    #[test]
+    fn sample_tables_are_expanded() {
+        let samples = parse_stbl(&stbl(&[]), 1000, 1000).unwrap();
+
+        assert_eq!(samples.len(), 2);
+        assert_eq!((samples[1].offset, samples[1].size), (108, 100));
+        assert_eq!((samples[1].info.start_time, samples[1].info.duration), (1001, 1001));
+    }
+
+    #[test]
+    fn hostile_sample_tables_are_rejected() {
+        let cases = [
+            // Entry counts larger than the boxes.
+            table_box(b"stsz", &[0, u32::MAX, 100]),
+            table_box(b"stts", &[u32::MAX, 2, 1001]),
+            table_box(b"stsc", &[0x1000_0000]),
+            // Truncated entries.
+            table_box(b"stsz", &[0, 2, 100]),
+            table_box(b"stsc", &[1, 1, 2]),
+            // A constant sample size larger than the file.
+            table_box(b"stsz", &[100, u32::MAX]),
+            // Chunks are 1-based.
+            table_box(b"stsc", &[1, 0, 2, 1]),
+        ];
+        for case in cases {
+            assert!(parse_stbl(&stbl(std::slice::from_ref(&case)), 1000, 1000).is_err(), "{:?}", &case[4..8]);
+        }
+
+        // Samples past the end of the file.
+        assert!(parse_stbl(&stbl(&[]), 1000, 200).is_err());
+        // Huge `stts` runs are only expanded as far as the samples go.
+        assert_eq!(parse_stbl(&stbl(&[table_box(b"stts", &[1, u32::MAX, 1])]), 1000, 1000).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn oversized_moov_is_rejected() {
+        let mut mp4 = mp4_box(b"ftyp", b"mp41");
+        mp4.extend(u32::MAX.to_be_bytes());
+        mp4.extend(b"moov");
+
+        let error = blocking_io::read_moov_index(&mut std::io::Cursor::new(mp4)).unwrap_err();
+
+        assert!(error.contains("exceeds the file"), "{error}");
+    }
+}
@@ -0,0 +1,135 @@
+//! Raw GPMF dumps, outside of any container.
+//!
+//! Two layouts are understood:
+//! - A single payload, i.e. the bytes of one MP4 sample, starting with `DEVC`.
+//! - A sequence of payloads, each preceded by its length as a big-endian
+//!   `u32`.
+
+use crate::{GpmfSampleInfo, GpmfTrack};
+
+/// Whether `bytes` starts like a raw GPMF dump of either layout.
+pub fn is_raw_gpmf(bytes: &[u8]) -> bool {
+    bytes.starts_with(b"DEVC") || bytes.get(4..8) == Some(b"DEVC")
+}
+
+/// Splits a raw GPMF dump into its payloads.
+pub fn split_payloads(bytes: &[u8]) -> Result<Vec<&[u8]>, String> {
+    if bytes.starts_with(b"DEVC") {
+        return Ok(vec![bytes]);
+    }
+
+    let mut payloads = Vec::new();
+    let mut rest = bytes;
+    while !rest.is_empty() {
+        let (length, tail) = rest
+            .split_first_chunk::<4>()
+            .ok_or_else(|| format!("Truncated payload length at offset {}", bytes.len() - rest.len()))?;
+        let length = u32::from_be_bytes(*length) as usize;
+        if tail.len() < length {
+            return Err(format!(
+                "Payload of {} bytes at offset {} exceeds the file",
+                length,
+                bytes.len() - rest.len()
+            ));
+        }
+        let (payload, tail) = tail.split_at(length);
+        payloads.push(payload);
+        rest = tail;
+    }
+    Ok(payloads)
+}
+
+/// Reads the payloads of the GPMF track of an MP4 stream as they are, without
+/// parsing them, e.g. to validate or dump files the parser rejects.
+///
+/// Each payload is handed to `f` as soon as it is read, so only one is held in
+/// memory at a time. Returns the number of payloads.
+pub fn for_each_mp4_payload<R, F>(reader: &mut R, mut f: F) -> Result<usize, String>
+where
+    R: std::io::Read + std::io::Seek,
+    F: FnMut(&[u8]),
+{
+    let moov_index = crate::mp4_index::blocking_io::read_moov_index(reader)
+        .map_err(|e| format!("Failed to read MP4 header: {}", e))?;
+    let track = moov_index.gpmf_track().ok_or(crate::mp4_index::NO_GPMF_TRACK)?;
+
+    for (sample_idx, location) in track.samples.iter().enumerate() {
+        let payload = crate::mp4_index::blocking_io::read_sample(reader, location)
+            .map_err(|e| format!("Failed to read sample {} for track {}: {}", sample_idx + 1, track.track_id, e))?;
+        f(&payload);
+    }
+    Ok(track.samples.len())
+}
+
+impl GpmfTrack {
+    /// Loads a raw GPMF dump as a track of one sample per payload, without
+    /// timing information.
+    pub fn from_raw_gpmf_bytes(bytes: &[u8]) -> Result<Self, String> {
+        Self::from_raw_gpmf_bytes_with(bytes, gpmf_parser::GpmfSampleParser::builtin())
+    }
+
+    /// [`GpmfTrack::from_raw_gpmf_bytes`] decoding the samples with `parser`.
+    pub fn from_raw_gpmf_bytes_with(bytes: &[u8], parser: &gpmf_parser::GpmfSampleParser) -> Result<Self, String> {
+        let gpmf_sample_infos = split_payloads(bytes)?
+            .into_iter()
+            .enumerate()
+            .map(|(index, payload)| {
+                GpmfSampleInfo::from_bytes(payload, None, parser)
+                    .map_err(|e| format!("Failed to create GPMF sample info from payload {}: {}", index, e))
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+
+        Ok(Self {
+            gpmf_sample_infos,
+            camera_info: None,
+            hilights: Vec::new(),
+            mp4_tracks: Vec::new(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use gpmf_parser::synth::DevcBuilder;
+
+    use crate::synth;
+
+    #[test]
+    fn single_payload_is_one_sample() {
+        let payload = DevcBuilder::typical(0).build();
+        assert!(is_raw_gpmf(&payload));
+
+        let track = GpmfTrack::from_raw_gpmf_bytes(&payload).unwrap();
+
+        assert_eq!(track.gpmf_sample_infos().len(), 1);
+        assert_eq!(track.gpmf_sample_infos()[0].gpmf_sample().accl().len(), 200);
+    }
+
+    #[test]
+    fn length_prefixed_payloads_are_split() {
+        let bytes = synth::length_prefixed(&[DevcBuilder::typical(0).build(), DevcBuilder::typical(1).build()]);
+        assert!(is_raw_gpmf(&bytes));
+
+        let track = GpmfTrack::from_raw_gpmf_bytes(&bytes).unwrap();
+
+        assert_eq!(track.gpmf_sample_infos().len(), 2);
+        let latitude = track.gpmf_sample_infos()[1].gpmf_sample().gps9().latitude;
+        assert!((latitude - 35.0001).abs() < 1e-5);
+
+        assert!(GpmfTrack::from_raw_gpmf_bytes(&bytes[..bytes.len() - 1]).is_err());
+        assert!(!is_raw_gpmf(b"\0\0\0\x18ftypmp41"));
+    }
+
+    #[test]
+    fn mp4_payloads_are_read_unparsed() {
+        let mp4 = synth::GoproMp4Builder::typical(2).build();
+
+        let mut payloads = Vec::new();
+        let count = for_each_mp4_payload(&mut std::io::Cursor::new(mp4), |payload| payloads.push(payload.to_vec())).unwrap();
+
+        assert_eq!(count, 2);
+        assert_eq!(payloads, vec![DevcBuilder::typical(0).build(), DevcBuilder::typical(1).build()]);
+    }
+}
@@ -0,0 +1,67 @@
+//! Time ranges of a track over which per-frame values share a property.
+
+/// A time range, in _s_, over which every frame was classified as `kind`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Segment<K> {
+    pub kind: K,
+    pub start: f64,
+    pub end: f64,
+}
+
+impl<K> Segment<K> {
+    pub fn duration(&self) -> f64 {
+        self.end - self.start
+    }
+}
+
+/// Merges runs of consecutive `(start, end, kind)` frames with the same kind
+/// into [`Segment`]s, dropping unclassified frames and segments shorter than
+/// `min_duration` _s_.
+pub(crate) fn merge_frames<K: PartialEq>(
+    frames: impl IntoIterator<Item = (f64, f64, Option<K>)>,
+    min_duration: f64,
+) -> Vec<Segment<K>> {
+    let mut segments: Vec<Segment<K>> = Vec::new();
+    let mut current: Option<Segment<K>> = None;
+    for (start, end, kind) in frames {
+        match (&mut current, kind) {
+            (Some(segment), Some(kind)) if segment.kind == kind => segment.end = end,
+            (_, kind) => {
+                segments.extend(current.take());
+                current = kind.map(|kind| Segment { kind, start, end });
+            }
+        }
+    }
+    segments.extend(current);
+    segments.retain(|segment| segment.duration() >= min_duration);
+    segments
+}
+
+impl crate::GpmfTrack {
+    /// Each value of a per-sample series with the `(start, end)` in _s_ of
+    /// the slice of its payload it covers. Samples that didn't come from an
+    /// MP4 track are skipped.
+    pub(crate) fn timed_frames<'a, T: Copy, S: AsRef<[T]>>(
+        &'a self,
+        series: impl Fn(&'a gpmf_parser::GpmfSample) -> S,
+    ) -> Vec<(f64, f64, T)> {
+        self.gpmf_sample_infos()
+            .iter()
+            .filter_map(|info| {
+                let values = series(info.gpmf_sample());
+                let values = values.as_ref();
+                let (_, end) = info.time_range()?;
+                let timestamps = info.timestamps(values.len())?;
+                let ends = timestamps.iter().skip(1).copied().chain([end]);
+                let frames: Vec<_> = timestamps
+                    .iter()
+                    .zip(ends)
+                    .zip(values)
+                    .map(|((&start, end), &value)| (start, end, value))
+                    .collect();
+                Some(frames)
+            })
+            .flatten()
+            .collect()
+    }
+}
@@ -0,0 +1,341 @@
+//! Overview of the tracks, devices and streams of a GoPro file.
+
+use gpmf_parser::klv::{Fourcc, Value};
+use gpmf_parser::{Gps9, Klv, StreamKlvs};
+
+use crate::mp4_index::MoovIndex;
+use crate::GpmfTrack;
+
+/// A track of an MP4 file, whether or not it carries GPMF.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mp4TrackSummary {
+    pub track_id: u32,
+    /// `hdlr` handler type, e.g. `vide`, `soun` or `meta`.
+    pub handler_type: String,
+    /// `hdlr` name, e.g. `GoPro MET`.
+    pub handler_name: String,
+    pub sample_count: usize,
+    /// In _s_.
+    pub duration: f64,
+    /// Whether this is the track GPMF is read from.
+    pub is_gpmf: bool,
+}
+
+/// Lists the tracks of an MP4 stream. Tracks loaded with
+/// [`GpmfTrack::from_mp4_stream`] already list them in
+/// [`GpmfTrack::mp4_tracks`].
+pub fn read_mp4_tracks<R: std::io::Read + std::io::Seek>(reader: &mut R) -> Result<Vec<Mp4TrackSummary>, String> {
+    let moov_index = crate::mp4_index::blocking_io::read_moov_index(reader)?;
+    Ok(mp4_tracks_of(&moov_index))
+}
+
+pub(crate) fn mp4_tracks_of(moov_index: &MoovIndex) -> Vec<Mp4TrackSummary> {
+    let gpmf_track_id = moov_index.gpmf_track().map(|track| track.track_id);
+
+    moov_index
+        .tracks
+        .iter()
+        .map(|track| Mp4TrackSummary {
+            track_id: track.track_id,
+            handler_type: String::from_utf8_lossy(&track.handler_type).into_owned(),
+            handler_name: track.handler_name.trim().to_string(),
+            sample_count: track.samples.len(),
+            duration: track
+                .samples
+                .iter()
+                .map(|sample| sample.info.duration as f64 / sample.info.timescale.max(1) as f64)
+                .sum(),
+            is_gpmf: Some(track.track_id) == gpmf_track_id,
+        })
+        .collect()
+}
+
+/// A `STRM` of a device, aggregated over all samples of a track.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StreamSummary {
+    /// FourCC of the stream's data KLV, e.g. `ACCL`.
+    pub fourcc: Fourcc,
+    /// `STNM`.
+    pub name: Option<String>,
+    /// `SIUN`, or `UNIT` if the stream has no SI units, one entry per axis
+    /// joined with `,`.
+    pub units: Option<String>,
+    /// `TYPE` for struct streams, otherwise the type character of the data
+    /// KLV, e.g. `s`.
+    pub value_type: String,
+    /// `SCAL`, one value per element or a single one for all. Empty if the
+    /// stream isn't scaled.
+    pub scale: Vec<f64>,
+    /// Total repeat of the data KLVs.
+    pub sample_count: u64,
+    /// Minimum and maximum of each element of the samples, e.g. each axis of
+    /// `ACCL`, with `SCAL` applied. Empty for non-numeric streams.
+    pub ranges: Vec<(f64, f64)>,
+    /// Total duration in _s_ of the samples the stream appears in. `None` if
+    /// the track has no timing.
+    pub duration: Option<f64>,
+}
+
+impl StreamSummary {
+    /// Measured rate in _Hz_.
+    pub fn rate(&self) -> Option<f64> {
+        let duration = self.duration?;
+        (duration > 0.0).then(|| self.sample_count as f64 / duration)
+    }
+}
+
+/// A `DEVC`, identified by `DVID`, aggregated over all samples of a track.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeviceSummary {
+    /// `DVID`.
+    pub id: Option<u32>,
+    /// `DVNM`.
+    pub name: Option<String>,
+    /// In order of first appearance.
+    pub streams: Vec<StreamSummary>,
+}
+
+/// Coverage of the `GPS9` stream.
+#[derive(Debug, Clone, Default)]
+pub struct GpsSummary {
+    /// Number of samples with a `GPS9` stream.
+    pub sample_count: usize,
+    /// Number of samples with a 2D or 3D fix.
+    pub fix_count: usize,
+    /// The first fix of the track.
+    pub first_fix: Option<Gps9>,
+    /// The last fix of the track.
+    pub last_fix: Option<Gps9>,
+}
+
+impl GpsSummary {
+    /// Fraction of samples with a `GPS9` stream that have a fix, in `[0, 1]`.
+    /// `0` when there are none.
+    pub fn coverage(&self) -> f64 {
+        if self.sample_count == 0 {
+            return 0.0;
+        }
+        self.fix_count as f64 / self.sample_count as f64
+    }
+}
+
+/// Devices, streams and GPS coverage of a [`GpmfTrack`].
+#[derive(Debug, Clone)]
+pub struct TrackSummary {
+    pub sample_count: usize,
+    /// In _s_. `None` if the track has no timing.
+    pub duration: Option<f64>,
+    /// In order of first appearance.
+    pub devices: Vec<DeviceSummary>,
+    pub gps: GpsSummary,
+}
+
+impl GpmfTrack {
+    /// Every track of the MP4 file this track was read from, with
+    /// [`GpmfTrack::from_mp4_stream`] or [`GpmfTrack::from_async_mp4_reader`].
+    /// Empty for other sources.
+    pub fn mp4_tracks(&self) -> &[Mp4TrackSummary] {
+        &self.mp4_tracks
+    }
+
+    /// Summarizes the devices and streams found across all samples.
+    pub fn summary(&self) -> TrackSummary {
+        let mut devices: Vec<DeviceSummary> = Vec::new();
+        let mut gps = GpsSummary::default();
+        let mut duration = None;
+
+        for sample_info in self.gpmf_sample_infos() {
+            let sample_duration = sample_info.time_range().map(|(start, end)| end - start);
+            if let Some(sample_duration) = sample_duration {
+                *duration.get_or_insert(0.0) += sample_duration;
+            }
+
+            for devc in sample_info.klvs() {
+                let Value::Nested(children) = devc.value() else {
+                    continue;
+                };
+                let id = children.iter().find_map(|klv| match (klv.header().fourcc().as_bytes(), klv.value()) {
+                    (b"DVID", Value::U32(v)) => v.first().copied(),
+                    _ => None,
+                });
+                let device = match devices.iter().position(|device| device.id == id) {
+                    Some(index) => &mut devices[index],
+                    None => {
+                        let name = children.iter().find_map(|klv| match (klv.header().fourcc().as_bytes(), klv.value()) {
+                            (b"DVNM", Value::Ascii(name)) => Some(trim(name)),
+                            _ => None,
+                        });
+                        devices.push(DeviceSummary { id, name, streams: Vec::new() });
+                        devices.last_mut().unwrap()
+                    }
+                };
+
+                for strm in children.iter().filter(|klv| klv.header().fourcc().as_bytes() == b"STRM") {
+                    if let Value::Nested(strm_children) = strm.value() {
+                        add_stream(&mut device.streams, strm_children, sample_duration);
+                    }
+                }
+            }
+
+            let sample = sample_info.gpmf_sample();
+            if sample.decoded(Fourcc(*b"GPS9")).is_some() {
+                gps.sample_count += 1;
+                let gps9 = sample.gps9();
+                if gps9.fix >= 2 {
+                    gps.fix_count += 1;
+                    gps.first_fix.get_or_insert(*gps9);
+                    gps.last_fix = Some(*gps9);
+                }
+            }
+        }
+
+        TrackSummary {
+            sample_count: self.gpmf_sample_infos().len(),
+            duration,
+            devices,
+            gps,
+        }
+    }
+}
+
+/// Adds the `STRM` with the given children to `streams`.
+fn add_stream(streams: &mut Vec<StreamSummary>, children: &[Klv], sample_duration: Option<f64>) {
+    let Some(stream_klvs) = StreamKlvs::new(children) else {
+        return;
+    };
+    let data = stream_klvs.data();
+    let fourcc = data.header().fourcc();
+    let sample_count = stream_klvs.sample_count();
+
+    let scale = stream_klvs.sticky("SCAL").and_then(|klv| klv.value().numbers()).unwrap_or_default();
+    let type_str = stream_klvs.sticky_text("TYPE");
+
+    if let Some(stream) = streams.iter_mut().find(|stream| stream.fourcc == fourcc) {
+        stream.sample_count += sample_count;
+        extend_ranges(&mut stream.ranges, stream_klvs.data_klvs(), type_str, &scale);
+        if let (Some(duration), Some(sample_duration)) = (&mut stream.duration, sample_duration) {
+            *duration += sample_duration;
+        }
+        return;
+    }
+
+    let units = stream_klvs
+        .units()
+        .map(|units| units.iter().map(|unit| trim(unit)).collect::<Vec<_>>().join(","));
+
+    let mut ranges = Vec::new();
+    extend_ranges(&mut ranges, stream_klvs.data_klvs(), type_str, &scale);
+
+    streams.push(StreamSummary {
+        fourcc,
+        name: stream_klvs.sticky_text("STNM").map(trim),
+        units,
+        value_type: type_str.map_or_else(|| data.header().tsr().value_type().as_char().to_string(), trim),
+        scale,
+        sample_count,
+        ranges,
+        duration: sample_duration,
+    });
+}
+
+/// Widens the per-element minimum and maximum in `ranges` to cover the
+/// values of `data_klvs`, divided by `scale`. Struct values are split into
+/// elements with `type_str`.
+fn extend_ranges<'a>(
+    ranges: &mut Vec<(f64, f64)>,
+    data_klvs: impl Iterator<Item = &'a Klv>,
+    type_str: Option<&str>,
+    scale: &[f64],
+) {
+    for klv in data_klvs {
+        let samples = match klv.value() {
+            Value::Complex(value) => type_str.and_then(|type_str| value.structs(type_str).ok()),
+            value => value.numbers().map(|numbers| {
+                let axis_count = klv.header().tsr().axis_count().max(1);
+                numbers.chunks(axis_count).map(<[f64]>::to_vec).collect()
+            }),
+        };
+        for sample in samples.unwrap_or_default() {
+            for (j, &value) in sample.iter().enumerate() {
+                let value = match scale.len() {
+                    0 => value,
+                    len => value / scale[j % len],
+                };
+                match ranges.get_mut(j) {
+                    Some((min, max)) => {
+                        *min = min.min(value);
+                        *max = max.max(value);
+                    }
+                    None => ranges.push((value, value)),
+                }
+            }
+        }
+    }
+}
+
+fn trim(text: &str) -> String {
+    text.trim_end_matches('\0').trim().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use gpmf_parser::synth::DevcBuilder;
+
+    use crate::synth::GoproMp4Builder;
+
+    #[test]
+    fn summary_lists_devices_and_streams() {
+        let track = GpmfTrack::from_mp4_stream(&mut std::io::Cursor::new(GoproMp4Builder::typical(3).build())).unwrap();
+
+        let summary = track.summary();
+
+        assert_eq!(summary.sample_count, 3);
+        assert!((summary.duration.unwrap() - 3.003).abs() < 1e-9);
+        assert_eq!(summary.devices.len(), 1);
+        let device = &summary.devices[0];
+        assert_eq!(device.id, Some(1));
+        assert_eq!(device.name.as_deref(), Some("Camera"));
+
+        let accl = device.streams.iter().find(|stream| stream.fourcc == Fourcc(*b"ACCL")).unwrap();
+        assert_eq!(accl.units.as_deref(), Some("m/s²"));
+        assert_eq!(accl.value_type, "s");
+        assert_eq!(accl.sample_count, 600);
+        assert_eq!(accl.scale.len(), 1);
+        assert_eq!(accl.ranges.len(), 3);
+        let (min, max) = accl.ranges[0];
+        assert!(min <= max && (max - 9.81).abs() < 0.1, "{:?}", accl.ranges);
+        assert!((accl.rate().unwrap() - 199.8).abs() < 0.1);
+
+        assert_eq!(summary.gps.sample_count, 3);
+        assert_eq!(summary.gps.coverage(), 1.0);
+        assert!(summary.gps.last_fix.unwrap().latitude > summary.gps.first_fix.unwrap().latitude);
+    }
+
+    #[test]
+    fn mp4_tracks_are_listed() {
+        let mp4 = GoproMp4Builder::typical(2).build();
+
+        let tracks = read_mp4_tracks(&mut std::io::Cursor::new(&mp4)).unwrap();
+
+        assert_eq!(tracks.len(), 1);
+        assert_eq!(tracks[0].handler_type, "meta");
+        assert!(tracks[0].handler_name.contains("GoPro MET"));
+        assert_eq!(tracks[0].sample_count, 2);
+        assert!(tracks[0].is_gpmf);
+
+        let track = GpmfTrack::from_mp4_stream(&mut std::io::Cursor::new(mp4)).unwrap();
+        assert_eq!(track.mp4_tracks(), &tracks[..]);
+    }
+
+    #[test]
+    fn raw_tracks_have_no_rates() {
+        let track = GpmfTrack::from_raw_gpmf_bytes(&DevcBuilder::typical(0).build()).unwrap();
+
+        let summary = track.summary();
+
+        assert!(summary.duration.is_none());
+        assert!(summary.devices[0].streams.iter().all(|stream| stream.rate().is_none()));
+    }
+}
@@ -0,0 +1,235 @@
+//! Writers for minimal GoPro-style MP4 and JPEG files, for end-to-end tests.
+//!
+//! The output has the box layout GoPro cameras use for their telemetry
+//! track: `ftyp`, an `mdat` holding the GPMF payloads, then a `moov` with a
+//! single `meta` track whose handler is named `GoPro MET` and whose sample
+//! entry is `gpmd`. There is no video or audio track.
+//!
+//! [`gopro_jpeg`] embeds a payload the way GoPro stills do, and
+//! [`length_prefixed`] writes a raw GPMF dump of several payloads.
+//!
+//! Payloads are typically built with [`gpmf_parser::synth::DevcBuilder`].
+
+use gpmf_parser::synth::DevcBuilder;
+
+/// Builds a GoPro-style MP4 file in memory.
+#[derive(Debug, Clone)]
+pub struct GoproMp4Builder {
+    timescale: u32,
+    samples: Vec<(Vec<u8>, u32)>,
+    udta_gpmf: Option<Vec<u8>>,
+    hilights_ms: Vec<u32>,
+}
+
+impl Default for GoproMp4Builder {
+    fn default() -> Self {
+        Self {
+            timescale: 1000,
+            samples: Vec::new(),
+            udta_gpmf: None,
+            hilights_ms: Vec::new(),
+        }
+    }
+}
+
+impl GoproMp4Builder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// `sample_count` payloads from [`DevcBuilder::typical`], each lasting
+    /// 1001 units of a 1000 Hz timescale like GoPro's ~1 s GPMF samples.
+    pub fn typical(sample_count: u32) -> Self {
+        (0..sample_count).fold(Self::new(), |builder, i| {
+            builder.sample(DevcBuilder::typical(i).build(), 1001)
+        })
+    }
+
+    /// Units per second of the track and movie. Defaults to 1000.
+    pub fn timescale(mut self, timescale: u32) -> Self {
+        self.timescale = timescale;
+        self
+    }
+
+    /// Appends a GPMF payload lasting `duration` timescale units.
+    pub fn sample(mut self, payload: Vec<u8>, duration: u32) -> Self {
+        self.samples.push((payload, duration));
+        self
+    }
+
+    /// Writes `payload`, a sequence of KLVs such as `FMWR` and `MINF`, as the
+    /// global metadata in `moov/udta/GPMF`.
+    pub fn udta(mut self, payload: Vec<u8>) -> Self {
+        self.udta_gpmf = Some(payload);
+        self
+    }
+
+    /// Writes HiLight tags at the given times in _ms_ as a `moov/udta/HMMT`
+    /// box, like cameras before the HERO8 do.
+    pub fn hilights(mut self, times_ms: &[u32]) -> Self {
+        self.hilights_ms = times_ms.to_vec();
+        self
+    }
+
+    pub fn build(&self) -> Vec<u8> {
+        let ftyp = mp4_box(b"ftyp", b"mp41\0\0\0\0mp41isom");
+
+        let payloads: Vec<u8> = self.samples.iter().flat_map(|(payload, _)| payload.iter().copied()).collect();
+        let mdat = mp4_box(b"mdat", &payloads);
+
+        // One chunk per sample, laid out back to back in `mdat`.
+        let mut chunk_offsets = Vec::with_capacity(self.samples.len());
+        let mut offset = (ftyp.len() + 8) as u32;
+        for (payload, _) in &self.samples {
+            chunk_offsets.push(offset);
+            offset += payload.len() as u32;
+        }
+
+        let duration: u32 = self.samples.iter().map(|&(_, duration)| duration).sum();
+
+        let mut moov = [self.mvhd(duration), self.trak(duration, &chunk_offsets)].concat();
+        let mut udta = Vec::new();
+        if !self.hilights_ms.is_empty() {
+            let hmmt: Vec<u32> = std::iter::once(self.hilights_ms.len() as u32).chain(self.hilights_ms.iter().copied()).collect();
+            udta.extend(mp4_box(b"HMMT", &u32s(&hmmt)));
+        }
+        if let Some(udta_gpmf) = &self.udta_gpmf {
+            udta.extend(mp4_box(b"GPMF", udta_gpmf));
+        }
+        if !udta.is_empty() {
+            moov.extend(mp4_box(b"udta", &udta));
+        }
+        let moov = mp4_box(b"moov", &moov);
+
+        [ftyp, mdat, moov].concat()
+    }
+
+    /// Writes the file to `path`.
+    pub fn write_to(&self, path: impl AsRef<std::path::Path>) -> std::io::Result<()> {
+        std::fs::write(path, self.build())
+    }
+
+    fn mvhd(&self, duration: u32) -> Vec<u8> {
+        let mut fields: Vec<u8> = Vec::new();
+        fields.extend(u32s(&[0, 0, self.timescale, duration]));
+        fields.extend(0x0001_0000u32.to_be_bytes()); // rate 1.0
+        fields.extend(0x0100u16.to_be_bytes()); // volume 1.0
+        fields.extend([0; 10]);
+        fields.extend(identity_matrix());
+        fields.extend([0; 24]);
+        fields.extend(2u32.to_be_bytes()); // next_track_id
+        full_box(b"mvhd", &fields)
+    }
+
+    fn trak(&self, duration: u32, chunk_offsets: &[u32]) -> Vec<u8> {
+        let mut tkhd: Vec<u8> = Vec::new();
+        tkhd.extend(u32s(&[0, 0, 1, 0, duration, 0, 0]));
+        tkhd.extend([0; 8]); // layer, alternate_group, volume, reserved
+        tkhd.extend(identity_matrix());
+        tkhd.extend(u32s(&[0, 0])); // width, height
+        let tkhd = full_box_with_flags(b"tkhd", 0x000003, &tkhd);
+
+        let mdhd = full_box(b"mdhd", &[u32s(&[0, 0, self.timescale, duration]), vec![0x55, 0xC4, 0, 0]].concat());
+
+        let mut hdlr = u32s(&[0]);
+        hdlr.extend(b"meta");
+        hdlr.extend([0; 12]);
+        hdlr.extend(b"\tGoPro MET\0");
+        let hdlr = full_box(b"hdlr", &hdlr);
+
+        let dref = full_box(b"dref", &[u32s(&[1]), full_box_with_flags(b"url ", 0x000001, &[])].concat());
+        let dinf = mp4_box(b"dinf", &dref);
+
+        let mdia = mp4_box(b"mdia", &[mdhd, hdlr, mp4_box(b"minf", &[dinf, self.stbl(chunk_offsets)].concat())].concat());
+
+        mp4_box(b"trak", &[tkhd, mdia].concat())
+    }
+
+    fn stbl(&self, chunk_offsets: &[u32]) -> Vec<u8> {
+        let mut gpmd = vec![0; 6];
+        gpmd.extend(1u16.to_be_bytes()); // data_reference_index
+        gpmd.extend(0u32.to_be_bytes());
+        let stsd = full_box(b"stsd", &[u32s(&[1]), mp4_box(b"gpmd", &gpmd)].concat());
+
+        // Run-length encode the durations.
+        let mut runs: Vec<(u32, u32)> = Vec::new();
+        for &(_, duration) in &self.samples {
+            match runs.last_mut() {
+                Some((count, last)) if *last == duration => *count += 1,
+                _ => runs.push((1, duration)),
+            }
+        }
+        let stts: Vec<u32> = std::iter::once(runs.len() as u32)
+            .chain(runs.iter().flat_map(|&(count, duration)| [count, duration]))
+            .collect();
+
+        let stsz: Vec<u32> = [0, self.samples.len() as u32]
+            .into_iter()
+            .chain(self.samples.iter().map(|(payload, _)| payload.len() as u32))
+            .collect();
+        let stco: Vec<u32> = std::iter::once(chunk_offsets.len() as u32).chain(chunk_offsets.iter().copied()).collect();
+
+        mp4_box(b"stbl", &[
+            stsd,
+            full_box(b"stts", &u32s(&stts)),
+            full_box(b"stsc", &u32s(&[1, 1, 1, 1])),
+            full_box(b"stsz", &u32s(&stsz)),
+            full_box(b"stco", &u32s(&stco)),
+        ].concat())
+    }
+}
+
+/// A minimal JPEG file carrying `gpmf` in GoPro APP6 segments of at most
+/// `segment_size` bytes of GPMF each, between the usual APP1 and the image
+/// data. The image itself is not decodable.
+pub fn gopro_jpeg(gpmf: &[u8], segment_size: usize) -> Vec<u8> {
+    let segment = |marker: u8, payload: &[u8]| {
+        let mut bytes = vec![0xFF, marker];
+        bytes.extend(((payload.len() + 2) as u16).to_be_bytes());
+        bytes.extend(payload);
+        bytes
+    };
+
+    let mut jpeg = vec![0xFF, 0xD8];
+    jpeg.extend(segment(0xE1, b"Exif\0\0"));
+    for chunk in gpmf.chunks(segment_size) {
+        jpeg.extend(segment(0xE6, &[crate::jpeg::APP6_SIGNATURE, chunk].concat()));
+    }
+    jpeg.extend(segment(0xDA, &[0; 10]));
+    jpeg.extend([0x12, 0x34, 0xFF, 0x00, 0x56]); // Entropy-coded data with a stuffed 0xFF.
+    jpeg.extend([0xFF, 0xD9]);
+    jpeg
+}
+
+/// A raw GPMF dump of `payloads`, each preceded by its length as a
+/// big-endian `u32`.
+pub fn length_prefixed(payloads: &[Vec<u8>]) -> Vec<u8> {
+    payloads
+        .iter()
+        .flat_map(|payload| [&(payload.len() as u32).to_be_bytes()[..], payload].concat())
+        .collect()
+}
+
+fn mp4_box(box_type: &[u8; 4], payload: &[u8]) -> Vec<u8> {
+    let mut bytes = ((payload.len() + 8) as u32).to_be_bytes().to_vec();
+    bytes.extend(box_type);
+    bytes.extend(payload);
+    bytes
+}
+
+/// A version 0 full box.
+fn full_box(box_type: &[u8; 4], fields: &[u8]) -> Vec<u8> {
+    full_box_with_flags(box_type, 0, fields)
+}
+
+fn full_box_with_flags(box_type: &[u8; 4], flags: u32, fields: &[u8]) -> Vec<u8> {
+    mp4_box(box_type, &[&flags.to_be_bytes()[..], fields].concat())
+}
+
+fn u32s(values: &[u32]) -> Vec<u8> {
+    values.iter().flat_map(|v| v.to_be_bytes()).collect()
+}
+
+fn identity_matrix() -> Vec<u8> {
+    u32s(&[0x0001_0000, 0, 0, 0, 0x0001_0000, 0, 0, 0, 0x4000_0000])
+}
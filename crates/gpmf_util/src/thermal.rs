@@ -0,0 +1,109 @@
+//! Camera temperature over a track, from the `TMPC` of each payload.
+
+use crate::GpmfTrack;
+
+/// Summary of the temperature over a track.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TemperatureReport {
+    /// Time, in _s_, of the first and last readings.
+    pub start: f64,
+    pub end: f64,
+    /// In _°C_.
+    pub min: f32,
+    pub max: f32,
+    /// Last reading, in _°C_.
+    pub last: f32,
+    /// Least-squares slope of the readings, in _°C/min_.
+    pub trend_per_minute: f32,
+}
+
+impl TemperatureReport {
+    /// Builds a report from `(time in s, °C)` readings. `None` when there are
+    /// none.
+    pub fn from_series(series: &[(f64, f32)]) -> Option<Self> {
+        let (&(start, first), &(end, last)) = (series.first()?, series.last()?);
+
+        let n = series.len() as f64;
+        let mean_t = series.iter().map(|&(t, _)| t).sum::<f64>() / n;
+        let mean_c = series.iter().map(|&(_, c)| c as f64).sum::<f64>() / n;
+        let covariance: f64 = series.iter().map(|&(t, c)| (t - mean_t) * (c as f64 - mean_c)).sum();
+        let variance: f64 = series.iter().map(|&(t, _)| (t - mean_t).powi(2)).sum();
+        let slope = if variance > 0.0 { covariance / variance } else { 0.0 };
+
+        let temperatures = series.iter().map(|&(_, c)| c);
+        Some(Self {
+            start,
+            end,
+            min: temperatures.clone().fold(first, f32::min),
+            max: temperatures.fold(first, f32::max),
+            last,
+            trend_per_minute: (slope * 60.0) as f32,
+        })
+    }
+
+    /// Time, in _s_ after the last reading, until `limit` _°C_ is reached if
+    /// the trend continues. `Some(0.0)` if it already was, `None` if the
+    /// temperature isn't rising.
+    pub fn time_to_reach(&self, limit: f32) -> Option<f64> {
+        if self.last >= limit {
+            return Some(0.0);
+        }
+        (self.trend_per_minute > 0.0).then(|| ((limit - self.last) / self.trend_per_minute) as f64 * 60.0)
+    }
+}
+
+impl GpmfTrack {
+    /// The `TMPC` of each payload in _°C_, paired with the payload's start
+    /// time in _s_. Samples without a `TMPC` or that didn't come from an MP4
+    /// track are skipped.
+    pub fn temperature_series(&self) -> Vec<(f64, f32)> {
+        self.gpmf_sample_infos()
+            .iter()
+            .filter_map(|info| Some((info.time_range()?.0, info.gpmf_sample().tmpc()?)))
+            .collect()
+    }
+
+    /// `None` when the track has no `TMPC`.
+    pub fn temperature_report(&self) -> Option<TemperatureReport> {
+        TemperatureReport::from_series(&self.temperature_series())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use gpmf_parser::synth::DevcBuilder;
+    use gpmf_parser::Vec3;
+
+    use crate::tests::track_of;
+
+    #[test]
+    fn report_extrapolates_rising_temperature() {
+        let gyro = [Vec3 { x: 0.0, y: 0.0, z: 0.0 }];
+        let payloads: Vec<Vec<u8>> = (0..4)
+            .map(|i| DevcBuilder::new(1, "Camera").tmpc(50.0 + i as f32 * 0.5).gyro(&gyro).build())
+            .collect();
+        let track = track_of(&payloads);
+
+        let series = track.temperature_series();
+        assert_eq!(series.len(), 4);
+        assert!((series[3].0 - 3.003).abs() < 1e-9);
+
+        let report = track.temperature_report().unwrap();
+        assert_eq!((report.min, report.max, report.last), (50.0, 51.5, 51.5));
+        // 0.5 °C per 1.001 s.
+        assert!((report.trend_per_minute - 0.5 / 1.001 * 60.0).abs() < 1e-3);
+        let remaining = report.time_to_reach(60.0).unwrap();
+        assert!((remaining - 8.5 / 0.5 * 1.001).abs() < 1e-2, "got {remaining}");
+        assert_eq!(report.time_to_reach(51.0), Some(0.0));
+    }
+
+    #[test]
+    fn steady_temperature_never_reaches_limit() {
+        let report = TemperatureReport::from_series(&[(0.0, 45.0), (10.0, 45.0)]).unwrap();
+        assert_eq!(report.time_to_reach(60.0), None);
+        assert!(TemperatureReport::from_series(&[]).is_none());
+        assert!(track_of(&[]).temperature_report().is_none());
+    }
+}